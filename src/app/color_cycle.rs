@@ -0,0 +1,61 @@
+use std::time::Instant;
+
+const DEFAULT_SPEED: f64 = 8.0;
+const SPEED_STEP: f64 = 1.5;
+const MIN_SPEED: f64 = 0.5;
+const MAX_SPEED: f64 = 128.0;
+
+/// Rotates the palette over time by shifting color indices, independently
+/// of the fractal geometry. Speed is in color indices per second and its
+/// sign gives the direction.
+pub struct ColorCycle {
+    pub enabled: bool,
+    pub speed: f64,
+    offset: f64,
+    last_update: Instant,
+}
+
+impl ColorCycle {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            speed: DEFAULT_SPEED,
+            offset: 0.0,
+            last_update: Instant::now(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.last_update = Instant::now();
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed * SPEED_STEP).clamp(-MAX_SPEED, MAX_SPEED);
+    }
+
+    pub fn slower(&mut self) {
+        let magnitude = (self.speed.abs() / SPEED_STEP).max(MIN_SPEED);
+        self.speed = magnitude.copysign(self.speed);
+    }
+
+    pub fn reverse(&mut self) {
+        self.speed = -self.speed;
+    }
+
+    /// Advances by the wall-clock time since the last call and returns the
+    /// offset to apply, or `None` while cycling is off.
+    pub fn advance(&mut self) -> Option<f64> {
+        let now = Instant::now();
+        let dt = now.duration_since(self.last_update).as_secs_f64();
+        self.last_update = now;
+
+        if !self.enabled {
+            return None;
+        }
+
+        // Left unwrapped so the renderer can wrap it to each scheme's span
+        self.offset += self.speed * dt;
+        Some(self.offset)
+    }
+}
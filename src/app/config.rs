@@ -0,0 +1,182 @@
+use std::io;
+use std::path::PathBuf;
+
+use num_complex::Complex;
+
+use crate::export::{ExportFormat, ExportSettings};
+use crate::fractal::field::FieldValue;
+use crate::fractal::julia::DEFAULT_MAX_ITERATIONS;
+use crate::fractal::polynomial;
+use crate::utils::log::{LOG_ENV, Level};
+
+/// Range of target frame rates accepted from `--fps`.
+const FPS_RANGE: std::ops::RangeInclusive<f64> = 1.0..=240.0;
+/// Highest `--max-iterations`, which bounds the per-frame histogram and
+/// the reference orbit kept for perturbation.
+const MAX_ITERATION_CAP: u32 = 1_000_000;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub export: ExportSettings,
+    pub palette_dir: PathBuf,
+    pub palette_seed: Option<u64>,
+    pub trap_glyph: Option<PathBuf>,
+    pub trap_position: Option<Complex<f64>>,
+    /// Coefficients by power for the root-finding formulas
+    pub polynomial: Option<Vec<f64>>,
+    pub target_fps: f64,
+    /// Iteration cap at full quality, used for exports
+    pub max_iterations: u32,
+    /// Logging is off unless a level is set here or in `JULIA_LOG`, or a
+    /// log file is given
+    pub log_level: Option<Level>,
+    pub log_file: Option<PathBuf>,
+    /// Key bindings replacing the defaults for the actions they name
+    pub keymap: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            export: ExportSettings::default(),
+            palette_dir: PathBuf::from("palettes"),
+            palette_seed: None,
+            trap_glyph: None,
+            trap_position: None,
+            polynomial: None,
+            target_fps: 20.0,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            log_level: None,
+            log_file: None,
+            keymap: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn from_args() -> io::Result<Self> {
+        let mut config = Self::parse(std::env::args().skip(1))?;
+        if config.log_level.is_none()
+            && let Ok(value) = std::env::var(LOG_ENV) {
+            config.log_level = Some(parse_level(&value)?);
+        }
+        if config.log_level.is_none() && config.log_file.is_some() {
+            config.log_level = Some(Level::Info);
+        }
+        Ok(config)
+    }
+
+    pub fn parse(mut args: impl Iterator<Item = String>) -> io::Result<Self> {
+        let mut config = Config::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--export-size" => {
+                    let value = expect_value(&arg, args.next())?;
+                    let (width, height) = parse_size(&value)
+                        .ok_or_else(|| invalid(format!("invalid size '{}', expected WIDTHxHEIGHT", value)))?;
+                    config.export.width = width;
+                    config.export.height = height;
+                }
+                "--export-format" => {
+                    let value = expect_value(&arg, args.next())?;
+                    config.export.format = ExportFormat::parse(&value)
+                        .ok_or_else(|| invalid(format!("unknown export format '{}', expected npy or csv", value)))?;
+                }
+                "--export-values" => {
+                    let value = expect_value(&arg, args.next())?;
+                    config.export.value = match value.as_str() {
+                        "iterations" => FieldValue::Iterations,
+                        "smooth" => FieldValue::Smooth,
+                        "distance" => FieldValue::Distance,
+                        "trap" => FieldValue::Trap,
+                        "root" => FieldValue::Root,
+                        _ => return Err(invalid(format!("unknown export values '{}', expected iterations, smooth, distance, trap or root", value))),
+                    };
+                }
+                "--contour-levels" => {
+                    let value = expect_value(&arg, args.next())?;
+                    config.export.contour_levels = value.parse().ok().filter(|&n| n > 0)
+                        .ok_or_else(|| invalid(format!("invalid contour level count '{}'", value)))?;
+                }
+                "--export-dir" => {
+                    config.export.directory = PathBuf::from(expect_value(&arg, args.next())?);
+                }
+                "--palettes" => {
+                    config.palette_dir = PathBuf::from(expect_value(&arg, args.next())?);
+                }
+                "--palette-seed" => {
+                    let value = expect_value(&arg, args.next())?;
+                    config.palette_seed = Some(value.parse()
+                        .map_err(|_| invalid(format!("invalid palette seed '{}'", value)))?);
+                }
+                "--trap-glyph" => {
+                    config.trap_glyph = Some(PathBuf::from(expect_value(&arg, args.next())?));
+                }
+                "--trap-position" => {
+                    let value = expect_value(&arg, args.next())?;
+                    config.trap_position = Some(parse_complex(&value)
+                        .ok_or_else(|| invalid(format!("invalid trap position '{}', expected RE,IM", value)))?);
+                }
+                "--polynomial" => {
+                    let value = expect_value(&arg, args.next())?;
+                    config.polynomial = Some(polynomial::parse(&value)
+                        .map_err(|err| invalid(format!("invalid polynomial '{}': {}", value, err)))?);
+                }
+                "--fps" => {
+                    let value = expect_value(&arg, args.next())?;
+                    config.target_fps = value.parse().ok().filter(|fps: &f64| FPS_RANGE.contains(fps))
+                        .ok_or_else(|| invalid(format!(
+                            "invalid frame rate '{}', expected {} to {}", value, FPS_RANGE.start(), FPS_RANGE.end()
+                        )))?;
+                }
+                "--max-iterations" => {
+                    let value = expect_value(&arg, args.next())?;
+                    config.max_iterations = value.parse().ok().filter(|n| (1..=MAX_ITERATION_CAP).contains(n))
+                        .ok_or_else(|| invalid(format!(
+                            "invalid iteration cap '{}', expected 1 to {}", value, MAX_ITERATION_CAP
+                        )))?;
+                }
+                "--log" => {
+                    config.log_file = Some(PathBuf::from(expect_value(&arg, args.next())?));
+                }
+                "--log-level" => {
+                    config.log_level = Some(parse_level(&expect_value(&arg, args.next())?)?);
+                }
+                "--keymap" => {
+                    config.keymap = Some(PathBuf::from(expect_value(&arg, args.next())?));
+                }
+                _ => return Err(invalid(format!("unknown argument '{}'", arg))),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+fn expect_value(flag: &str, value: Option<String>) -> io::Result<String> {
+    value.ok_or_else(|| invalid(format!("{} requires a value", flag)))
+}
+
+fn parse_size(value: &str) -> Option<(u16, u16)> {
+    let (width, height) = value.split_once('x')?;
+    let width = width.parse().ok().filter(|&w| w > 0)?;
+    let height = height.parse().ok().filter(|&h| h > 0)?;
+    Some((width, height))
+}
+
+fn parse_complex(value: &str) -> Option<Complex<f64>> {
+    let (re, im) = value.split_once(',')?;
+    let re: f64 = re.trim().parse().ok().filter(|re: &f64| re.is_finite())?;
+    let im: f64 = im.trim().parse().ok().filter(|im: &f64| im.is_finite())?;
+    Some(Complex::new(re, im))
+}
+
+fn parse_level(value: &str) -> io::Result<Level> {
+    Level::parse(value)
+        .ok_or_else(|| invalid(format!("unknown log level '{}', expected error, warn, info or debug", value)))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
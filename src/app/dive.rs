@@ -0,0 +1,112 @@
+use std::time::Instant;
+
+use num_complex::Complex;
+
+use crate::fractal::parameters::FractalParameters;
+
+/// Zoom factor per second.
+const ZOOM_RATE: f64 = 1.5;
+/// How quickly the view closes in on the target, per second.
+const PAN_RATE: f64 = 2.0;
+/// Seconds between searches for a new target.
+const RETARGET_INTERVAL: f64 = 1.0;
+/// Longest step taken at once, so a stall or pause doesn't cause a jump.
+const MAX_STEP: f64 = 0.25;
+
+pub enum DiveStep {
+    Idle,
+    Move { zoom: f64, pan: Complex<f64> },
+    /// Out of precision or detail; the dive should restart elsewhere
+    Exhausted,
+}
+
+/// Continuously zooms toward detailed boundary regions. The target is kept
+/// as an offset from the view center and re-picked once a second, so the
+/// dive steers away from regions that have turned flat. The search runs on
+/// the render worker alongside a frame; the dive holds still until the
+/// first result arrives.
+pub struct Dive {
+    pub enabled: bool,
+    target: Option<Complex<f64>>,
+    /// Set when the last search found nothing but flat regions
+    flat: bool,
+    /// Pans made since the pending search was requested, whose result is
+    /// relative to the view it was given
+    drift: Complex<f64>,
+    last_update: Instant,
+    last_search: Option<Instant>,
+}
+
+impl Dive {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            target: None,
+            flat: false,
+            drift: Complex::new(0.0, 0.0),
+            last_update: Instant::now(),
+            last_search: None,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.last_update = Instant::now();
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.target = None;
+        self.flat = false;
+        self.last_search = None;
+    }
+
+    /// Whether the next frame should also search for a new target. Called
+    /// when a frame is requested; a true result counts as the search.
+    pub fn wants_search(&mut self) -> bool {
+        let now = Instant::now();
+        if !self.enabled || self.last_search.is_some_and(|at| now.duration_since(at).as_secs_f64() < RETARGET_INTERVAL) {
+            return false;
+        }
+        self.last_search = Some(now);
+        self.drift = Complex::new(0.0, 0.0);
+        true
+    }
+
+    /// Takes the result of a search, the offset of the most detailed region
+    /// from the center of the view searched.
+    pub fn set_target(&mut self, detail: Option<Complex<f64>>) {
+        if !self.enabled {
+            return;
+        }
+        match detail {
+            Some(offset) => self.target = Some(offset - self.drift),
+            None => self.flat = true,
+        }
+    }
+
+    pub fn advance(&mut self, params: &FractalParameters) -> DiveStep {
+        let now = Instant::now();
+        let dt = now.duration_since(self.last_update).as_secs_f64().min(MAX_STEP);
+        self.last_update = now;
+
+        if !self.enabled {
+            return DiveStep::Idle;
+        }
+
+        // Zoom stops at the precision limit, where retargeting pans would
+        // round away
+        if self.flat || params.zoom * ZOOM_RATE > params.formula.max_zoom() {
+            self.reset();
+            return DiveStep::Exhausted;
+        }
+
+        let Some(target) = &mut self.target else {
+            return DiveStep::Idle;
+        };
+        let pan = *target * (1.0 - (-PAN_RATE * dt).exp());
+        *target -= pan;
+        self.drift += pan;
+        DiveStep::Move { zoom: ZOOM_RATE.powf(dt), pan }
+    }
+}
@@ -0,0 +1,416 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Everything a key can do. Input is translated to actions through a
+/// `Keymap`, so the help panel always lists the bindings in effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    NextScheme,
+    NewCosinePalette,
+    SavePalette,
+    NextColorMode,
+    NextInteriorMode,
+    NextTrapShape,
+    MoveTrap,
+    NextFractal,
+    NewSet,
+    ZoomIn,
+    ZoomOut,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    ToggleDive,
+    ToggleHud,
+    ExportField,
+    ExportContours,
+    TogglePause,
+    ToggleCycling,
+    CycleSlower,
+    CycleFaster,
+    ReverseCycling,
+    ToggleHelp,
+}
+
+impl Action {
+    /// In the order the help panel lists them.
+    pub const ALL: [Action; 26] = [
+        Action::Quit,
+        Action::NextScheme,
+        Action::NewCosinePalette,
+        Action::SavePalette,
+        Action::NextColorMode,
+        Action::NextInteriorMode,
+        Action::NextTrapShape,
+        Action::MoveTrap,
+        Action::NextFractal,
+        Action::NewSet,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::PanLeft,
+        Action::PanRight,
+        Action::PanUp,
+        Action::PanDown,
+        Action::ToggleDive,
+        Action::ToggleHud,
+        Action::ExportField,
+        Action::ExportContours,
+        Action::TogglePause,
+        Action::ToggleCycling,
+        Action::CycleSlower,
+        Action::CycleFaster,
+        Action::ReverseCycling,
+        Action::ToggleHelp,
+    ];
+
+    /// Name used in keymap files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::NextScheme => "next-scheme",
+            Action::NewCosinePalette => "new-cosine-palette",
+            Action::SavePalette => "save-palette",
+            Action::NextColorMode => "next-color-mode",
+            Action::NextInteriorMode => "next-interior-mode",
+            Action::NextTrapShape => "next-trap-shape",
+            Action::MoveTrap => "move-trap",
+            Action::NextFractal => "next-fractal",
+            Action::NewSet => "new-set",
+            Action::ZoomIn => "zoom-in",
+            Action::ZoomOut => "zoom-out",
+            Action::PanLeft => "pan-left",
+            Action::PanRight => "pan-right",
+            Action::PanUp => "pan-up",
+            Action::PanDown => "pan-down",
+            Action::ToggleDive => "toggle-dive",
+            Action::ToggleHud => "toggle-hud",
+            Action::ExportField => "export-field",
+            Action::ExportContours => "export-contours",
+            Action::TogglePause => "toggle-pause",
+            Action::ToggleCycling => "toggle-cycling",
+            Action::CycleSlower => "cycle-slower",
+            Action::CycleFaster => "cycle-faster",
+            Action::ReverseCycling => "reverse-cycling",
+            Action::ToggleHelp => "toggle-help",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::NextScheme => "Change color scheme",
+            Action::NewCosinePalette => "New random cosine palette",
+            Action::SavePalette => "Save cosine palette",
+            Action::NextColorMode => "Change color mode",
+            Action::NextInteriorMode => "Change interior coloring",
+            Action::NextTrapShape => "Change orbit trap shape",
+            Action::MoveTrap => "Move orbit trap",
+            Action::NextFractal => "Change fractal formula",
+            Action::NewSet => "New random Julia set",
+            Action::ZoomIn => "Zoom in",
+            Action::ZoomOut => "Zoom out",
+            Action::PanLeft => "Pan left",
+            Action::PanRight => "Pan right",
+            Action::PanUp => "Pan up",
+            Action::PanDown => "Pan down",
+            Action::ToggleDive => "Toggle dive mode",
+            Action::ToggleHud => "Toggle info overlay",
+            Action::ExportField => "Export iteration data",
+            Action::ExportContours => "Export SVG contours",
+            Action::TogglePause => "Pause/resume transition",
+            Action::ToggleCycling => "Toggle palette cycling",
+            Action::CycleSlower => "Slower cycling",
+            Action::CycleFaster => "Faster cycling",
+            Action::ReverseCycling => "Reverse cycling direction",
+            Action::ToggleHelp => "Show/hide this help",
+        }
+    }
+}
+
+/// A key together with the modifiers held with it. Shift is folded into
+/// the character for character keys, so `T` and `shift+t` are the same
+/// binding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers = modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                let upper = c.to_uppercase().next().unwrap_or(c);
+                Self { code: KeyCode::Char(upper), modifiers: modifiers - KeyModifiers::SHIFT }
+            }
+            _ => Self { code, modifiers },
+        }
+    }
+
+    pub fn key(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    /// Parses bindings like `q`, `space`, `ctrl+c` or `alt+shift+left`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        // A trailing `+` is the key itself, as in `+` or `ctrl++`
+        let (modifiers, key) = match spec.strip_suffix('+') {
+            Some(rest) if rest.is_empty() || rest.ends_with('+') => (rest.strip_suffix('+').unwrap_or(rest), "+"),
+            _ => spec.rsplit_once('+').unwrap_or(("", spec)),
+        };
+
+        let mut flags = KeyModifiers::NONE;
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            flags |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "insert" => KeyCode::Insert,
+                "delete" => KeyCode::Delete,
+                name => KeyCode::F(name.strip_prefix('f')?.parse().ok().filter(|n| (1..=24).contains(n))?),
+            },
+        };
+
+        Some(Self::new(code, flags))
+    }
+}
+
+impl From<KeyEvent> for KeyBinding {
+    fn from(event: KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [(KeyModifiers::CONTROL, "ctrl+"), (KeyModifiers::ALT, "alt+"), (KeyModifiers::SHIFT, "shift+")] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            other => write!(f, "{}", format!("{:?}", other).to_lowercase()),
+        }
+    }
+}
+
+/// Bindings from keys to actions. Several keys may share an action; a key
+/// bound to several actions is a conflict, resolved in favor of the
+/// binding listed first.
+pub struct Keymap {
+    bindings: Vec<(KeyBinding, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = vec![
+            (KeyBinding::key(KeyCode::Char('q')), Action::Quit),
+            (KeyBinding::new(KeyCode::Char('c'), KeyModifiers::CONTROL), Action::Quit),
+            (KeyBinding::key(KeyCode::Char('c')), Action::NextScheme),
+            (KeyBinding::key(KeyCode::Char('C')), Action::NewCosinePalette),
+            (KeyBinding::key(KeyCode::Char('w')), Action::SavePalette),
+            (KeyBinding::key(KeyCode::Char('m')), Action::NextColorMode),
+            (KeyBinding::key(KeyCode::Char('i')), Action::NextInteriorMode),
+            (KeyBinding::key(KeyCode::Char('t')), Action::NextTrapShape),
+            (KeyBinding::key(KeyCode::Char('T')), Action::MoveTrap),
+            (KeyBinding::key(KeyCode::Char('f')), Action::NextFractal),
+            (KeyBinding::key(KeyCode::Char('n')), Action::NewSet),
+            (KeyBinding::key(KeyCode::Char('+')), Action::ZoomIn),
+            (KeyBinding::key(KeyCode::Char('=')), Action::ZoomIn),
+            (KeyBinding::key(KeyCode::Char('-')), Action::ZoomOut),
+            (KeyBinding::key(KeyCode::Left), Action::PanLeft),
+            (KeyBinding::key(KeyCode::Right), Action::PanRight),
+            (KeyBinding::key(KeyCode::Up), Action::PanUp),
+            (KeyBinding::key(KeyCode::Down), Action::PanDown),
+            (KeyBinding::key(KeyCode::Char('d')), Action::ToggleDive),
+            (KeyBinding::key(KeyCode::Char('v')), Action::ToggleHud),
+            (KeyBinding::key(KeyCode::Char('e')), Action::ExportField),
+            (KeyBinding::key(KeyCode::Char('s')), Action::ExportContours),
+            (KeyBinding::key(KeyCode::Char(' ')), Action::TogglePause),
+            (KeyBinding::key(KeyCode::Char('p')), Action::ToggleCycling),
+            (KeyBinding::key(KeyCode::Char('[')), Action::CycleSlower),
+            (KeyBinding::key(KeyCode::Char(']')), Action::CycleFaster),
+            (KeyBinding::key(KeyCode::Char('r')), Action::ReverseCycling),
+            (KeyBinding::key(KeyCode::Char('h')), Action::ToggleHelp),
+        ];
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Loads a keymap file of `action = key, key, ...` lines; lines
+    /// starting with `#` are comments. Actions it names take only the keys
+    /// listed, none if the list is empty; the rest keep their default keys.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|(line, message)| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: {}", path.display(), line, message))
+        })
+    }
+
+    fn parse(text: &str) -> Result<Self, (usize, String)> {
+        let mut bindings = Vec::new();
+        let mut configured = Vec::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: String| (index + 1, message);
+            let (name, keys) = line.split_once('=')
+                .ok_or_else(|| error("expected 'action = keys'".to_string()))?;
+            let action = Action::parse(name.trim())
+                .ok_or_else(|| error(format!("unknown action '{}'", name.trim())))?;
+            configured.push(action);
+
+            for spec in keys.split(',').map(str::trim).filter(|spec| !spec.is_empty()) {
+                let key = KeyBinding::parse(spec)
+                    .ok_or_else(|| error(format!("invalid key '{}'", spec)))?;
+                bindings.push((key, action));
+            }
+        }
+
+        // Configured keys come first, so they win conflicts with defaults
+        bindings.extend(Self::default().bindings.into_iter().filter(|(_, action)| !configured.contains(action)));
+        Ok(Self { bindings })
+    }
+
+    pub fn action(&self, key: KeyBinding) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|&(_, action)| action)
+    }
+
+    /// One message per key bound to more than one action.
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        for (i, (key, action)) in self.bindings.iter().enumerate() {
+            if self.bindings[..i].iter().any(|(earlier, _)| earlier == key) {
+                continue;
+            }
+            let others: Vec<&str> = self.bindings[i + 1..]
+                .iter()
+                .filter(|(other, other_action)| other == key && other_action != action)
+                .map(|(_, other_action)| other_action.name())
+                .collect();
+            if !others.is_empty() {
+                conflicts.push(format!("'{}' is bound to {} and {}; using {}", key, action.name(), others.join(", "), action.name()));
+            }
+        }
+        conflicts
+    }
+
+    /// One entry per bound action, with all of its keys, for the help
+    /// panel. Keys lost to a conflict are left out.
+    pub fn help(&self) -> Vec<(String, &'static str)> {
+        Action::ALL
+            .iter()
+            .filter_map(|&action| {
+                let keys: Vec<String> = self.bindings
+                    .iter()
+                    .filter(|&&(key, bound)| bound == action && self.action(key) == Some(action))
+                    .map(|(key, _)| key.to_string())
+                    .collect();
+                (!keys.is_empty()).then(|| (keys.join(" / "), action.description()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> KeyBinding {
+        KeyBinding::key(KeyCode::Char(c))
+    }
+
+    fn ctrl(c: char) -> KeyBinding {
+        KeyBinding::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    #[test]
+    fn parses_key_specs() {
+        assert_eq!(KeyBinding::parse("q"), Some(key('q')));
+        assert_eq!(KeyBinding::parse("+"), Some(key('+')));
+        assert_eq!(KeyBinding::parse("ctrl++"), Some(ctrl('+')));
+        assert_eq!(KeyBinding::parse("Ctrl+c"), Some(ctrl('c')));
+        assert_eq!(KeyBinding::parse("shift+t"), KeyBinding::parse("T"));
+        assert_eq!(KeyBinding::parse("space"), Some(key(' ')));
+        assert_eq!(KeyBinding::parse("alt+shift+left"), Some(KeyBinding::new(KeyCode::Left, KeyModifiers::ALT | KeyModifiers::SHIFT)));
+        assert_eq!(KeyBinding::parse("f1"), Some(KeyBinding::key(KeyCode::F(1))));
+        assert_eq!(KeyBinding::parse("F24"), Some(KeyBinding::key(KeyCode::F(24))));
+
+        for spec in ["", "f0", "f25", "fx", "meta+q", "ctrl+", "ctrl+spacebar"] {
+            assert_eq!(KeyBinding::parse(spec), None, "{:?}", spec);
+        }
+    }
+
+    #[test]
+    fn file_bindings_replace_defaults() {
+        let keymap = Keymap::parse("# comment\n\nquit = x, ctrl+q\nzoom-in =\n").unwrap();
+        assert_eq!(keymap.action(key('x')), Some(Action::Quit));
+        assert_eq!(keymap.action(ctrl('q')), Some(Action::Quit));
+        assert_eq!(keymap.action(key('q')), None);
+        assert_eq!(keymap.action(ctrl('c')), None);
+        assert_eq!(keymap.action(key('+')), None);
+        assert_eq!(keymap.action(key('-')), Some(Action::ZoomOut));
+
+        assert_eq!(Keymap::parse("quit = q\nwarp = w").err().map(|(line, _)| line), Some(2));
+        assert_eq!(Keymap::parse("\n\nquit = meta+q").err().map(|(line, _)| line), Some(3));
+        assert_eq!(Keymap::parse("quit q").err().map(|(line, _)| line), Some(1));
+    }
+
+    #[test]
+    fn reports_conflicts() {
+        assert!(Keymap::default().conflicts().is_empty());
+
+        // Within the file, the first binding wins
+        let keymap = Keymap::parse("quit = x\nzoom-in = x").unwrap();
+        assert_eq!(keymap.action(key('x')), Some(Action::Quit));
+        assert_eq!(keymap.conflicts().len(), 1);
+
+        // Against a default, the file wins and the default key is hidden
+        let keymap = Keymap::parse("quit = c").unwrap();
+        assert_eq!(keymap.action(key('c')), Some(Action::Quit));
+        assert_eq!(keymap.conflicts(), ["'c' is bound to quit and next-scheme; using quit"]);
+        assert!(keymap.help().iter().all(|(_, description)| *description != Action::NextScheme.description()));
+    }
+}
@@ -1 +1,6 @@
+pub mod color_cycle;
+pub mod config;
+pub mod dive;
+pub mod keymap;
+pub mod scheduler;
 pub mod state;
@@ -0,0 +1,99 @@
+use std::time::{Duration, Instant};
+
+use crate::fractal::progressive::PASSES;
+use crate::log_debug;
+
+/// Lowest iteration cap adaptive quality will drop to.
+const MIN_ITERATIONS: u32 = 32;
+/// Factor applied to the cap after a frame that overran its budget.
+const SLOWDOWN: f64 = 0.8;
+/// Factor applied to the cap after a frame with plenty of headroom.
+const SPEEDUP: f64 = 1.1;
+/// Render time, as a fraction of the budget, that counts as headroom.
+const HEADROOM: f64 = 0.5;
+/// Weight of the newest sample in the smoothed frame rate.
+const FPS_SMOOTHING: f64 = 0.2;
+
+/// Paces the main loop to a target frame rate and adapts quality to how
+/// long frames take to render. When a frame overruns its budget the
+/// iteration cap is lowered, and once it bottoms out the sample density is
+/// halved. With headroom, density is restored first, then the cap is raised
+/// back toward the configured one.
+pub struct FrameScheduler {
+    frame_time: Duration,
+    frame_start: Instant,
+    /// Iteration cap for the next frame
+    pub max_iterations: u32,
+    /// Block size the next frame is refined down to
+    pub sample_step: u16,
+    quality_cap: u32,
+    last_render: Option<Instant>,
+    /// Smoothed rate of finished frames
+    pub fps: f64,
+}
+
+impl FrameScheduler {
+    pub fn new(target_fps: f64, max_iterations: u32) -> Self {
+        Self {
+            frame_time: Duration::from_secs_f64(1.0 / target_fps),
+            frame_start: Instant::now(),
+            max_iterations,
+            sample_step: 1,
+            quality_cap: max_iterations,
+            last_render: None,
+            fps: 0.0,
+        }
+    }
+
+    /// Time left before the next frame is due, for waiting on input.
+    pub fn remaining(&self) -> Duration {
+        self.frame_time.saturating_sub(self.frame_start.elapsed())
+    }
+
+    /// Starts the next frame if it is due.
+    pub fn next_frame(&mut self) -> bool {
+        if !self.remaining().is_zero() {
+            return false;
+        }
+        self.frame_start = Instant::now();
+        true
+    }
+
+    /// Records a finished frame and how long the worker spent on it.
+    pub fn render_finished(&mut self, render_time: Duration) {
+        let now = Instant::now();
+        if let Some(last) = self.last_render.replace(now) {
+            let rate = 1.0 / now.duration_since(last).as_secs_f64().max(1e-3);
+            self.fps = if self.fps == 0.0 { rate } else { self.fps + (rate - self.fps) * FPS_SMOOTHING };
+        }
+
+        let render_time = render_time.as_secs_f64();
+        let budget = self.frame_time.as_secs_f64();
+        let min_iterations = MIN_ITERATIONS.min(self.quality_cap);
+        let coarsest = PASSES[0];
+
+        let previous = (self.max_iterations, self.sample_step);
+        if render_time > budget {
+            if self.max_iterations > min_iterations {
+                self.max_iterations = ((self.max_iterations as f64 * SLOWDOWN) as u32).max(min_iterations);
+            } else if self.sample_step < coarsest {
+                self.sample_step *= 2;
+            }
+        } else if self.sample_step > 1 {
+            // Halving the step quadruples the samples, so it waits until the
+            // finer frame would fit
+            if render_time * 4.0 < budget {
+                self.sample_step /= 2;
+            }
+        } else if render_time < budget * HEADROOM {
+            let raised = (self.max_iterations as f64 * SPEEDUP).ceil() as u32;
+            self.max_iterations = raised.min(self.quality_cap);
+        }
+        if (self.max_iterations, self.sample_step) != previous {
+            log_debug!(
+                "Render took {:.0} ms, iteration cap {} -> {}, sample step {} -> {}",
+                render_time * 1000.0, previous.0, self.max_iterations, previous.1, self.sample_step
+            );
+        }
+    }
+}
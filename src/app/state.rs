@@ -1,53 +1,303 @@
+use std::sync::Arc;
 use std::time::Instant;
 
+use num_complex::Complex;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::app::color_cycle::ColorCycle;
+use crate::app::dive::{Dive, DiveStep};
+use crate::fractal::formula::FractalKind;
+use crate::fractal::interior::InteriorMode;
 use crate::fractal::parameters::FractalParameters;
-use crate::ui::colors::ColorScheme;
-use crate::utils::constants::{MIN_TRANSITION_TIME, MAX_TRANSITION_TIME};
+use crate::fractal::trap::{Glyph, OrbitTrap, TrapShape};
+use crate::{log_debug, log_info};
+use crate::ui::colors::{ColorMode, ColorScheme};
+use crate::ui::palette::Gradient;
+use crate::ui::palette::cosine::CosinePalette;
+use crate::utils::{MIN_TRANSITION_TIME, MAX_TRANSITION_TIME, MIN_ZOOM, STATUS_DURATION};
 
 pub struct AppState {
     pub current_fractal: FractalParameters,
     pub next_fractal: FractalParameters,
     pub transition_start: Instant,
     pub transition_time: f64,
-    pub color_scheme: ColorScheme,
+    /// Complexity measured for the current transition, in `[0, 1]`
+    pub complexity: f64,
+    pub color_schemes: Vec<ColorScheme>,
+    pub color_scheme_index: usize,
+    pub palette_rng: StdRng,
+    pub color_mode: ColorMode,
+    pub interior_mode: InteriorMode,
+    pub trap_glyph: Arc<Glyph>,
+    /// Trap position chosen by the user, kept by every new set instead
+    /// of a random one
+    pub trap_center: Option<Complex<f64>>,
+    /// User-supplied roots for the root-finding formulas
+    pub polynomial_roots: Option<Vec<Complex<f64>>>,
+    pub color_cycle: ColorCycle,
+    pub dive: Dive,
+    /// Set while the transition clock stands still, during a pause or a
+    /// dive
+    pub frozen_at: Option<Instant>,
+    pub paused: bool,
     pub show_help: bool,
+    pub show_hud: bool,
+    pub status: Option<(String, Instant)>,
 }
 
 impl AppState {
     pub fn new() -> Self {
+        let mut palette_rng = StdRng::from_entropy();
+        let mut color_schemes = ColorScheme::built_in();
+        color_schemes.push(ColorScheme::Cosine(CosinePalette::from_seed(palette_rng.r#gen())));
+
+        let current_fractal = FractalParameters::random(FractalKind::Julia);
+        let next_fractal = current_fractal.successor();
+
         Self {
-            current_fractal: FractalParameters::random(),
-            next_fractal: FractalParameters::random(),
+            current_fractal,
+            next_fractal,
             transition_start: Instant::now(),
             transition_time: 10.0,
-            color_scheme: ColorScheme::Rainbow,
+            complexity: 0.5,
+            color_schemes,
+            color_scheme_index: 0,
+            palette_rng,
+            color_mode: ColorMode::EscapeTime,
+            interior_mode: InteriorMode::Flat,
+            trap_glyph: Arc::new(Glyph::default()),
+            trap_center: None,
+            polynomial_roots: None,
+            color_cycle: ColorCycle::new(),
+            dive: Dive::new(),
+            frozen_at: None,
+            paused: false,
             show_help: false,
+            show_hud: false,
+            status: None,
         }
     }
 
+    pub fn add_palettes(&mut self, gradients: Vec<Gradient>) {
+        // Loaded palettes go before the procedural slot, which stays last
+        let at = self.color_schemes.len() - 1;
+        self.color_schemes.splice(at..at, gradients.into_iter().map(|g| ColorScheme::Gradient(Arc::new(g))));
+    }
+
+    /// Makes procedural palettes reproducible: the first one is generated
+    /// from `seed` and later ones from a generator seeded with it.
+    pub fn seed_palettes(&mut self, seed: u64) {
+        self.palette_rng = StdRng::seed_from_u64(seed);
+        let last = self.color_schemes.len() - 1;
+        self.color_schemes[last] = ColorScheme::Cosine(CosinePalette::from_seed(seed));
+    }
+
+    /// Switches to the procedural slot with a freshly generated palette.
+    pub fn new_cosine_palette(&mut self) -> CosinePalette {
+        let palette = CosinePalette::from_seed(self.palette_rng.r#gen());
+        let last = self.color_schemes.len() - 1;
+        self.color_schemes[last] = ColorScheme::Cosine(palette);
+        self.color_scheme_index = last;
+        palette
+    }
+
+    pub fn color_scheme(&self) -> &ColorScheme {
+        &self.color_schemes[self.color_scheme_index]
+    }
+
     pub fn next_color_scheme(&mut self) {
-        self.color_scheme = self.color_scheme.next();
+        self.color_scheme_index = (self.color_scheme_index + 1) % self.color_schemes.len();
+        if matches!(self.color_scheme(), ColorScheme::Cosine(_)) {
+            self.new_cosine_palette();
+        }
+    }
+
+    pub fn next_color_mode(&mut self) {
+        self.color_mode = self.color_mode.next(self.current_fractal.formula.kind().finds_roots());
+    }
+
+    pub fn next_interior_mode(&mut self) {
+        self.interior_mode = self.interior_mode.next();
+    }
+
+    pub fn trap_shape(&self) -> &TrapShape {
+        &self.current_fractal.trap.shape
+    }
+
+    pub fn next_trap_shape(&mut self) {
+        let shape = match self.trap_shape() {
+            TrapShape::Point => TrapShape::Line,
+            TrapShape::Line => TrapShape::Cross,
+            TrapShape::Cross => TrapShape::Circle,
+            TrapShape::Circle => TrapShape::Glyph(self.trap_glyph.clone()),
+            TrapShape::Glyph(_) => TrapShape::Point,
+        };
+        self.current_fractal.trap.shape = shape.clone();
+        self.next_fractal.trap.shape = shape;
+    }
+
+    /// Picks a new trap placement that the current transition animates to.
+    pub fn move_trap(&mut self) {
+        self.next_fractal.trap = OrbitTrap::random(self.trap_shape().clone());
+        self.keep_user_settings();
+    }
+
+    /// Fixes the trap of both ends of the transition at `center`.
+    pub fn set_trap_center(&mut self, center: Complex<f64>) {
+        self.trap_center = Some(center);
+        self.current_fractal.trap.center = center;
+        self.keep_user_settings();
+    }
+
+    /// Puts back the trap position the user chose, which newly generated
+    /// sets would otherwise randomize.
+    fn keep_user_settings(&mut self) {
+        if let Some(center) = self.trap_center {
+            self.next_fractal.trap.center = center;
+        }
+    }
+
+    /// Gives a newly generated next set the user's polynomial, turned so
+    /// its roots still move during the transition.
+    fn anchor_roots(&mut self) {
+        if let Some(roots) = &self.polynomial_roots {
+            self.next_fractal.formula.set_rotated_roots(roots, &mut rand::thread_rng());
+        }
     }
 
     pub fn start_new_transition(&mut self, complexity: f64) {
         self.current_fractal = self.next_fractal.clone();
-        self.next_fractal = FractalParameters::random();
-        self.transition_start = Instant::now();
+        self.next_fractal = self.current_fractal.successor();
+        self.keep_user_settings();
+        self.anchor_roots();
+        self.transition_start = self.frozen_at.unwrap_or_else(Instant::now);
         self.update_transition_time(complexity);
     }
 
+    /// Cuts to a fresh set of the given kind and restarts the transition.
+    pub fn set_fractal_kind(&mut self, kind: FractalKind) {
+        let shape = self.trap_shape().clone();
+        self.current_fractal = FractalParameters::random(kind);
+        self.current_fractal.trap.shape = shape;
+        if let Some(center) = self.trap_center {
+            self.current_fractal.trap.center = center;
+        }
+        if let Some(roots) = &self.polynomial_roots {
+            self.current_fractal.formula.set_roots(roots);
+        }
+        self.next_fractal = self.current_fractal.successor();
+        self.keep_user_settings();
+        self.anchor_roots();
+        self.transition_start = self.frozen_at.unwrap_or_else(Instant::now);
+    }
+
+    /// Zooms both ends of the transition by `factor` so the change holds
+    /// while it plays, within what each formula can resolve.
+    pub fn zoom_by(&mut self, factor: f64) {
+        for params in [&mut self.current_fractal, &mut self.next_fractal] {
+            params.zoom = (params.zoom * factor).clamp(MIN_ZOOM, params.formula.max_zoom());
+        }
+    }
+
+    /// Pans both ends of the transition by a fraction of the visible width.
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        let view = self.current_fractal_interpolated(self.transition_elapsed());
+        let delta = Complex::new(dx, dy) * 3.0 / view.zoom;
+        self.current_fractal.pan(delta);
+        self.next_fractal.pan(delta);
+    }
+
+    /// Moves the dive along, cutting to a fresh set of the same kind when it
+    /// runs out of precision or detail. Returns whether it restarted.
+    pub fn advance_dive(&mut self) -> bool {
+        if self.is_paused() {
+            return false;
+        }
+
+        let view = self.current_fractal_interpolated(self.transition_elapsed());
+        match self.dive.advance(&view) {
+            DiveStep::Idle => false,
+            DiveStep::Move { zoom, pan } => {
+                self.zoom_by(zoom);
+                self.current_fractal.pan(pan);
+                self.next_fractal.pan(pan);
+                false
+            }
+            DiveStep::Exhausted => {
+                self.set_fractal_kind(self.current_fractal.formula.kind());
+                true
+            }
+        }
+    }
+
     pub fn update_transition_time(&mut self, complexity: f64) {
+        self.complexity = complexity;
         self.transition_time = MIN_TRANSITION_TIME + complexity * (MAX_TRANSITION_TIME - MIN_TRANSITION_TIME);
-        println!("Complexity: {:.2}, Time: {:.2}s", complexity, self.transition_time);
+        log_debug!("Complexity: {:.2}, Time: {:.2}s", complexity, self.transition_time);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.update_clock();
+    }
+
+    /// A transition changes `c`, which at dive depths replaces the view
+    /// entirely, so the transition stands still while diving.
+    pub fn toggle_dive(&mut self) {
+        self.dive.toggle();
+        self.update_clock();
+    }
+
+    /// Stops or restarts the transition clock. Restarting shifts the
+    /// transition start so that no progress is made while it stood still.
+    fn update_clock(&mut self) {
+        let frozen = self.paused || self.dive.enabled;
+        match (frozen, self.frozen_at) {
+            (true, None) => self.frozen_at = Some(Instant::now()),
+            (false, Some(frozen_at)) => {
+                self.transition_start += frozen_at.elapsed();
+                self.frozen_at = None;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn transition_elapsed(&self) -> f64 {
+        let now = self.frozen_at.unwrap_or_else(Instant::now);
+        now.duration_since(self.transition_start).as_secs_f64()
     }
 
     pub fn toggle_help(&mut self) {
         self.show_help = !self.show_help;
     }
 
+    pub fn toggle_hud(&mut self) {
+        self.show_hud = !self.show_hud;
+    }
+
+    pub fn set_status(&mut self, message: String) {
+        log_info!("{}", message);
+        self.status = Some((message, Instant::now()));
+    }
+
+    pub fn active_status(&self) -> Option<&str> {
+        match &self.status {
+            Some((message, shown_at)) if shown_at.elapsed().as_secs_f64() < STATUS_DURATION => Some(message),
+            _ => None,
+        }
+    }
+
     pub fn current_fractal_interpolated(&self, elapsed_seconds: f64) -> FractalParameters {
-        let progress = (elapsed_seconds / self.transition_time).min(1.0);
-        self.current_fractal.lerp(&self.next_fractal, progress)
+        self.current_fractal.lerp(&self.next_fractal, self.transition_progress(elapsed_seconds))
+    }
+
+    pub fn transition_progress(&self, elapsed_seconds: f64) -> f64 {
+        (elapsed_seconds / self.transition_time).min(1.0)
     }
 
     pub fn is_transition_complete(&self, elapsed_seconds: f64) -> bool {
@@ -0,0 +1,139 @@
+use crate::fractal::field::IterationField;
+
+/// A line segment in pixel coordinates.
+pub type Segment = ((f64, f64), (f64, f64));
+
+/// Traces the iso-line `level` through the field with marching squares.
+/// Saddle cells are disambiguated with the average of their four corners.
+pub fn marching_squares(field: &IterationField, level: f64) -> Vec<Segment> {
+    let mut segments = Vec::new();
+
+    if field.width < 2 || field.height < 2 {
+        return segments;
+    }
+
+    for y in 0..field.height - 1 {
+        for x in 0..field.width - 1 {
+            let tl = field.get(x, y);
+            let tr = field.get(x + 1, y);
+            let br = field.get(x + 1, y + 1);
+            let bl = field.get(x, y + 1);
+
+            let case = ((tl > level) as u8) << 3
+                | ((tr > level) as u8) << 2
+                | ((br > level) as u8) << 1
+                | (bl > level) as u8;
+
+            if case == 0 || case == 15 {
+                continue;
+            }
+
+            let (fx, fy) = (x as f64, y as f64);
+            let top = (fx + crossing(tl, tr, level), fy);
+            let right = (fx + 1.0, fy + crossing(tr, br, level));
+            let bottom = (fx + crossing(bl, br, level), fy + 1.0);
+            let left = (fx, fy + crossing(tl, bl, level));
+
+            let center_above = (tl + tr + br + bl) / 4.0 > level;
+
+            match case {
+                1 | 14 => segments.push((left, bottom)),
+                2 | 13 => segments.push((bottom, right)),
+                3 | 12 => segments.push((left, right)),
+                4 | 11 => segments.push((top, right)),
+                6 | 9 => segments.push((top, bottom)),
+                7 | 8 => segments.push((left, top)),
+                5 => {
+                    if center_above {
+                        segments.push((left, top));
+                        segments.push((bottom, right));
+                    } else {
+                        segments.push((left, bottom));
+                        segments.push((top, right));
+                    }
+                }
+                10 => {
+                    if center_above {
+                        segments.push((left, bottom));
+                        segments.push((top, right));
+                    } else {
+                        segments.push((left, top));
+                        segments.push((bottom, right));
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    segments
+}
+
+/// Fraction of the way from `a` to `b` at which the field crosses `level`.
+fn crossing(a: f64, b: f64, level: f64) -> f64 {
+    let delta = b - a;
+    if delta.abs() < f64::EPSILON {
+        0.5
+    } else {
+        ((level - a) / delta).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractal::field::FieldValue;
+
+    /// A 2×2 field with corners above the level where `case` has bits set,
+    /// in the order top left, top right, bottom right, bottom left.
+    fn cell(case: u8, high: f64) -> IterationField {
+        let mut field = IterationField::blank(2, 2, FieldValue::Smooth, 100);
+        let corners = [(0, 0, 3), (1, 0, 2), (1, 1, 1), (0, 1, 0)];
+        for (x, y, bit) in corners {
+            field.values[y * 2 + x] = if case >> bit & 1 == 1 { high } else { 0.0 };
+        }
+        field
+    }
+
+    #[test]
+    fn covers_every_case() {
+        let top = (0.5, 0.0);
+        let right = (1.0, 0.5);
+        let bottom = (0.5, 1.0);
+        let left = (0.0, 0.5);
+
+        for case in 0..16u8 {
+            let segments = marching_squares(&cell(case, 1.0), 0.5);
+
+            // Each edge whose corners straddle the level is crossed once, at
+            // its midpoint for corners of 0 and 1
+            let bit = |shift: u8| case >> shift & 1;
+            let mut expected: Vec<_> = [(3, 2, top), (2, 1, right), (1, 0, bottom), (3, 0, left)]
+                .into_iter()
+                .filter(|&(a, b, _)| bit(a) != bit(b))
+                .map(|(_, _, point)| point)
+                .collect();
+            let mut ends: Vec<_> = segments.iter().flat_map(|&(a, b)| [a, b]).collect();
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            ends.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert_eq!(ends, expected, "case {}", case);
+            assert_eq!(segments.len(), expected.len() / 2, "case {}", case);
+        }
+
+        // Saddles join the corners on the side of the level the center is
+        // on. With high corners of 3 the center is above, and edges are
+        // crossed a sixth of the way from their low corner.
+        assert_eq!(marching_squares(&cell(5, 1.0), 0.5), [(left, bottom), (top, right)]);
+        assert_eq!(marching_squares(&cell(10, 1.0), 0.5), [(left, top), (bottom, right)]);
+        let (near, far) = (1.0 / 6.0, 5.0 / 6.0);
+        assert_close(marching_squares(&cell(5, 3.0), 0.5), [((0.0, near), (near, 0.0)), ((far, 1.0), (1.0, far))]);
+        assert_close(marching_squares(&cell(10, 3.0), 0.5), [((0.0, far), (near, 1.0)), ((far, 0.0), (1.0, near))]);
+    }
+
+    fn assert_close<const N: usize>(actual: Vec<Segment>, expected: [Segment; N]) {
+        let points = |segments: &[Segment]| -> Vec<f64> { segments.iter().flat_map(|&((a, b), (c, d))| [a, b, c, d]).collect() };
+        let (actual_points, expected_points) = (points(&actual), points(&expected));
+        assert_eq!(actual_points.len(), expected_points.len(), "{:?}", actual);
+        assert!(actual_points.iter().zip(&expected_points).all(|(a, b)| (a - b).abs() < 1e-12), "{:?} != {:?}", actual, expected);
+    }
+}
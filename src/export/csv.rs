@@ -0,0 +1,25 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::fractal::field::{FieldValue, IterationField};
+
+/// Writes one CSV row per pixel row, with no header line.
+pub fn write_csv(path: &Path, field: &IterationField) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+
+    for row in field.rows() {
+        for (i, value) in row.iter().enumerate() {
+            if i > 0 {
+                out.write_all(b",")?;
+            }
+            match field.value {
+                FieldValue::Iterations => write!(out, "{}", *value as u32)?,
+                FieldValue::Smooth | FieldValue::Distance | FieldValue::Trap | FieldValue::Root => write!(out, "{:.6}", value)?,
+            }
+        }
+        out.write_all(b"\n")?;
+    }
+
+    out.flush()
+}
@@ -0,0 +1,32 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::fractal::field::IterationField;
+use crate::fractal::parameters::FractalParameters;
+
+pub fn write_metadata(path: &Path, params: &FractalParameters, field: &IterationField, data_path: &Path) -> io::Result<()> {
+    let data_file = data_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let json = format!(
+        "{{\n  \"data\": \"{}\",\n  \"values\": \"{}\",\n  \"formula\": \"{}\",\n  \"width\": {},\n  \"height\": {},\n  \"c\": [{:e}, {:e}],\n  \"zoom\": {:e},\n  \"x_offset\": {:e},\n  \"y_offset\": {:e},\n  \"x_offset_exact\": \"{}\",\n  \"y_offset_exact\": \"{}\",\n  \"max_iterations\": {}\n}}\n",
+        data_file,
+        field.value.name(),
+        params.formula,
+        field.width,
+        field.height,
+        params.c.re,
+        params.c.im,
+        params.zoom,
+        params.x_offset.to_f64(),
+        params.y_offset.to_f64(),
+        params.x_offset,
+        params.y_offset,
+        params.max_iterations,
+    );
+
+    fs::write(path, json)
+}
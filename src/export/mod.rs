@@ -0,0 +1,123 @@
+pub mod contour;
+pub mod csv;
+pub mod metadata;
+pub mod npy;
+pub mod svg;
+
+use std::fs::OpenOptions;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::fractal::field::{FieldValue, IterationField};
+use crate::fractal::parameters::FractalParameters;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Npy,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "npy" => Some(ExportFormat::Npy),
+            "csv" => Some(ExportFormat::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Npy => "npy",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportSettings {
+    pub width: u16,
+    pub height: u16,
+    pub format: ExportFormat,
+    pub value: FieldValue,
+    pub directory: PathBuf,
+    pub contour_levels: usize,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            width: 640,
+            height: 480,
+            format: ExportFormat::Npy,
+            value: FieldValue::Smooth,
+            directory: PathBuf::from("."),
+            contour_levels: 16,
+        }
+    }
+}
+
+/// Writes the escape values of `params` at the configured resolution, plus a
+/// JSON sidecar describing the view. Returns the path of the data file.
+pub fn export_field(params: &FractalParameters, settings: &ExportSettings) -> io::Result<PathBuf> {
+    let field = IterationField::compute(params, settings.width, settings.height, settings.value);
+
+    let (stem, data_path) = claim_path(&settings.directory, settings.format.extension())?;
+    let metadata_path = settings.directory.join(format!("{}.json", stem));
+
+    match settings.format {
+        ExportFormat::Npy => npy::write_npy(&data_path, &field)?,
+        ExportFormat::Csv => csv::write_csv(&data_path, &field)?,
+    }
+    metadata::write_metadata(&metadata_path, params, &field, &data_path)?;
+
+    Ok(data_path)
+}
+
+/// Traces evenly spaced iso-lines of the smooth escape field and writes
+/// them as an SVG. Returns the path of the SVG file.
+pub fn export_contours(params: &FractalParameters, settings: &ExportSettings) -> io::Result<PathBuf> {
+    let field = IterationField::compute(params, settings.width, settings.height, FieldValue::Smooth);
+
+    let (min, max) = field.values.iter().fold((f64::MAX, f64::MIN), |(min, max), &v| (min.min(v), max.max(v)));
+    let count = settings.contour_levels.max(1);
+    let levels: Vec<_> = (0..count)
+        .map(|i| {
+            let level = min + (max - min) * (i as f64 + 0.5) / count as f64;
+            (level, contour::marching_squares(&field, level))
+        })
+        .collect();
+
+    let (_, path) = claim_path(&settings.directory, "svg")?;
+    svg::write_svg(&path, field.width, field.height, &levels)?;
+
+    Ok(path)
+}
+
+/// Creates a new, empty `julia_<timestamp>` file with the given extension,
+/// adding a counter when exports land in the same millisecond, and returns
+/// its stem and path.
+fn claim_path(directory: &Path, extension: &str) -> io::Result<(String, PathBuf)> {
+    let base = format!("julia_{}", timestamp());
+    let mut counter = 0;
+    loop {
+        let stem = match counter {
+            0 => base.clone(),
+            n => format!("{}_{}", base, n),
+        };
+        let path = directory.join(format!("{}.{}", stem, extension));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(_) => return Ok((stem, path)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => counter += 1,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn timestamp() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
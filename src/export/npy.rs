@@ -0,0 +1,86 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::fractal::field::{FieldValue, IterationField};
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+const HEADER_ALIGNMENT: usize = 64;
+
+/// Writes the field as a version 1.0 `.npy` array of shape `(height, width)`.
+/// Iteration counts are stored as `<u4`, other values as `<f8`.
+pub fn write_npy(path: &Path, field: &IterationField) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+
+    let descr = match field.value {
+        FieldValue::Iterations => "<u4",
+        FieldValue::Smooth | FieldValue::Distance | FieldValue::Trap | FieldValue::Root => "<f8",
+    };
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': ({}, {}), }}",
+        descr, field.height, field.width
+    );
+
+    // magic + version (2) + header length (2) + header + '\n' must be aligned
+    let unpadded = MAGIC.len() + 4 + header.len() + 1;
+    let padding = (HEADER_ALIGNMENT - unpadded % HEADER_ALIGNMENT) % HEADER_ALIGNMENT;
+    header.extend(std::iter::repeat_n(' ', padding));
+    header.push('\n');
+
+    out.write_all(MAGIC)?;
+    out.write_all(&[1, 0])?;
+    out.write_all(&(header.len() as u16).to_le_bytes())?;
+    out.write_all(header.as_bytes())?;
+
+    for &value in &field.values {
+        match field.value {
+            FieldValue::Iterations => out.write_all(&(value as u32).to_le_bytes())?,
+            FieldValue::Smooth | FieldValue::Distance | FieldValue::Trap | FieldValue::Root => out.write_all(&value.to_le_bytes())?,
+        }
+    }
+
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written(value: FieldValue, width: u16, height: u16) -> Vec<u8> {
+        let mut field = IterationField::blank(width, height, value, 100);
+        for (i, v) in field.values.iter_mut().enumerate() {
+            *v = i as f64 + 0.5;
+        }
+        let path = std::env::temp_dir().join(format!("julia_npy_test_{}_{}.npy", std::process::id(), value.name()));
+        write_npy(&path, &field).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        bytes
+    }
+
+    /// Splits a file into its header text and data, checking the preamble.
+    fn split(bytes: &[u8]) -> (&str, &[u8]) {
+        assert_eq!(&bytes[..6], MAGIC);
+        assert_eq!(&bytes[6..8], &[1, 0]);
+        let length = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + length) % HEADER_ALIGNMENT, 0, "data is not aligned");
+        let header = std::str::from_utf8(&bytes[10..10 + length]).unwrap();
+        (header, &bytes[10 + length..])
+    }
+
+    #[test]
+    fn writes_padded_header_and_data() {
+        let bytes = written(FieldValue::Smooth, 3, 2);
+        let (header, data) = split(&bytes);
+        assert!(header.starts_with("{'descr': '<f8', 'fortran_order': False, 'shape': (2, 3), }"), "{}", header);
+        assert!(header.ends_with(" \n"));
+        let values: Vec<f64> = data.chunks(8).map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap())).collect();
+        assert_eq!(values, [0.5, 1.5, 2.5, 3.5, 4.5, 5.5]);
+
+        let bytes = written(FieldValue::Iterations, 1000, 1);
+        let (header, data) = split(&bytes);
+        assert!(header.contains("'descr': '<u4'") && header.contains("'shape': (1, 1000)"), "{}", header);
+        assert_eq!(data.len(), 4000);
+        assert_eq!(&data[4..8], &1u32.to_le_bytes());
+    }
+}
@@ -0,0 +1,39 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::contour::Segment;
+
+/// Writes one stroked path per contour level, colored along a hue ramp
+/// from the outermost level to the innermost.
+pub fn write_svg(path: &Path, width: u16, height: u16, levels: &[(f64, Vec<Segment>)]) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+        w = width,
+        h = height
+    )?;
+    writeln!(out, "  <rect width=\"100%\" height=\"100%\" fill=\"black\"/>")?;
+
+    for (index, (level, segments)) in levels.iter().enumerate() {
+        if segments.is_empty() {
+            continue;
+        }
+
+        let hue = 360.0 * index as f64 / levels.len() as f64;
+        write!(
+            out,
+            "  <path data-level=\"{:.3}\" fill=\"none\" stroke=\"hsl({:.0}, 80%, 60%)\" stroke-width=\"0.5\" d=\"",
+            level, hue
+        )?;
+        for ((x1, y1), (x2, y2)) in segments {
+            write!(out, "M{:.2} {:.2}L{:.2} {:.2}", x1, y1, x2, y2)?;
+        }
+        writeln!(out, "\"/>")?;
+    }
+
+    writeln!(out, "</svg>")?;
+    out.flush()
+}
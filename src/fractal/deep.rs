@@ -0,0 +1,97 @@
+use num_complex::Complex;
+
+use super::julia::pixel_offset;
+use super::parameters::FractalParameters;
+use super::perturbation::Perturbation;
+use super::precision::{BigComplex, DEEP_ZOOM, precision_for_zoom};
+
+/// Whether `params` is zoomed past f64's resolution with a formula that can
+/// be iterated in high precision.
+pub fn is_deep(params: &FractalParameters) -> bool {
+    params.zoom > DEEP_ZOOM && params.formula.supports_big()
+}
+
+/// Renderer for one deep frame: perturbation where the formula allows it,
+/// otherwise every pixel iterated in high precision.
+pub enum DeepRenderer {
+    Perturbation(Perturbation),
+    Direct(DeepView),
+}
+
+impl DeepRenderer {
+    pub fn new(params: &FractalParameters, bailout: f64) -> Self {
+        if params.formula.supports_perturbation() {
+            DeepRenderer::Perturbation(Perturbation::new(params, bailout))
+        } else {
+            DeepRenderer::Direct(DeepView::new(params))
+        }
+    }
+
+    pub fn iterate(&self, x: u16, y: u16, width: u16, height: u16, params: &FractalParameters, bailout: f64) -> DeepOrbit {
+        match self {
+            DeepRenderer::Perturbation(perturbation) => {
+                perturbation.iterate(pixel_offset(x, y, width, height, params), params)
+            }
+            DeepRenderer::Direct(view) => view.iterate(view.map_point(x, y, width, height, params), params, bailout),
+        }
+    }
+}
+
+/// High-precision pixel mapping for one frame. The center and `c` are
+/// converted once at the working precision for the zoom.
+pub struct DeepView {
+    center: BigComplex,
+    c: BigComplex,
+    bits: u32,
+}
+
+/// Where an orbit stopped: the iteration count, the final point and the
+/// derivative, with the last two rounded to f64.
+pub struct DeepOrbit {
+    pub iterations: u32,
+    pub z: Complex<f64>,
+    pub dz: Complex<f64>,
+}
+
+impl DeepView {
+    pub fn new(params: &FractalParameters) -> Self {
+        let bits = precision_for_zoom(params.zoom);
+        Self {
+            center: params.big_center(bits),
+            c: BigComplex::from_complex(params.c, bits),
+            bits,
+        }
+    }
+
+    pub fn map_point(&self, x: u16, y: u16, width: u16, height: u16, params: &FractalParameters) -> BigComplex {
+        let offset = pixel_offset(x, y, width, height, params);
+        &self.center + &BigComplex::from_complex(offset, self.bits)
+    }
+
+    /// Iterates from `z` until the orbit's squared modulus exceeds
+    /// `bailout`. The derivative only feeds distance estimates, so it is
+    /// tracked in f64.
+    pub fn iterate(&self, z: BigComplex, params: &FractalParameters, bailout: f64) -> DeepOrbit {
+        let formula = &params.formula;
+        let mut z = z;
+        let mut prev = BigComplex::from_complex(Complex::new(0.0, 0.0), self.bits);
+        let mut small = z.to_complex();
+        let mut small_prev = Complex::new(0.0, 0.0);
+        let mut dz = Complex::new(1.0, 0.0);
+        let mut i = 0;
+
+        while i < params.max_iterations && small.norm_sqr() <= bailout {
+            let Some(next) = formula.step_big(&z, &prev, &self.c) else {
+                break;
+            };
+            dz *= formula.derivative(small, small_prev, params.c);
+            prev = z;
+            z = next;
+            small_prev = small;
+            small = z.to_complex();
+            i += 1;
+        }
+
+        DeepOrbit { iterations: i, z: small, dz }
+    }
+}
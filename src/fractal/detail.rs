@@ -0,0 +1,48 @@
+use num_complex::Complex;
+
+use super::field::{FieldValue, IterationField};
+use super::julia::pixel_offset;
+use super::parameters::FractalParameters;
+
+/// Screen cells per sample along each axis.
+const SAMPLE_STEP: u16 = 4;
+/// Samples per block along each axis.
+const BLOCK: u16 = 4;
+/// Escape-time variance below which a block counts as flat.
+const MIN_VARIANCE: f64 = 1.0;
+
+/// Finds the most detailed part of the view by sampling escape times on a
+/// coarse grid and comparing their variance block by block, like
+/// `measure_complexity` but locally. Returns the block's offset from the
+/// view center, or `None` when every block is flat.
+pub fn find_detail(params: &FractalParameters, width: u16, height: u16) -> Option<Complex<f64>> {
+    let columns = (width / SAMPLE_STEP).max(BLOCK);
+    let rows = (height / SAMPLE_STEP).max(BLOCK);
+    let samples = IterationField::compute(params, columns, rows, FieldValue::Iterations);
+
+    let mut best: Option<(f64, u16, u16)> = None;
+    for by in 0..rows / BLOCK {
+        for bx in 0..columns / BLOCK {
+            let variance = block_variance(&samples, bx * BLOCK, by * BLOCK);
+            if variance >= MIN_VARIANCE && best.is_none_or(|(most, _, _)| variance > most) {
+                best = Some((variance, bx, by));
+            }
+        }
+    }
+
+    best.map(|(_, bx, by)| {
+        let x = bx * BLOCK + BLOCK / 2;
+        let y = by * BLOCK + BLOCK / 2;
+        pixel_offset(x, y, columns, rows, params)
+    })
+}
+
+fn block_variance(samples: &IterationField, left: u16, top: u16) -> f64 {
+    let values: Vec<f64> = (top..top + BLOCK)
+        .flat_map(|y| (left..left + BLOCK).map(move |x| (x, y)))
+        .map(|(x, y)| samples.get(x, y))
+        .collect();
+
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64
+}
@@ -0,0 +1,185 @@
+use super::deep::{DeepRenderer, is_deep};
+use super::formula::Formula;
+use super::interior::{InteriorMode, interior_value};
+use super::julia::{DISTANCE_BAILOUT, calculate_iterations, distance_estimate, distance_value, map_point_to_complex, orbit_trap, pixel_size, root_basin, smooth_iterations, smooth_value};
+use super::parameters::FractalParameters;
+use super::simd::quadratic_iterations;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldValue {
+    Iterations,
+    Smooth,
+    /// Distance to the set in pixels
+    Distance,
+    /// Closest approach of escaping orbits to the trap, infinite for
+    /// points that never escape
+    Trap,
+    /// Index of the converged root plus the fraction of the iteration
+    /// limit it took, infinite for points that never converge
+    Root,
+}
+
+impl FieldValue {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldValue::Iterations => "iterations",
+            FieldValue::Smooth => "smooth",
+            FieldValue::Distance => "distance",
+            FieldValue::Trap => "trap",
+            FieldValue::Root => "root",
+        }
+    }
+}
+
+/// Per-pixel escape values for one view, stored row-major.
+#[derive(Clone)]
+pub struct IterationField {
+    pub width: u16,
+    pub height: u16,
+    pub value: FieldValue,
+    /// Iteration cap the field was computed with; escape values at it
+    /// mark points on the set
+    pub max_iterations: u32,
+    pub values: Vec<f64>,
+    /// Interior shades in `[0, 1]`, NaN for escaping points. Empty when the
+    /// interior is drawn flat.
+    pub interior: Vec<f64>,
+}
+
+impl IterationField {
+    pub fn compute(params: &FractalParameters, width: u16, height: u16, value: FieldValue) -> Self {
+        let sampler = Sampler::new(params, width, height, value);
+        let columns: Vec<u16> = (0..width).collect();
+        let mut values = Vec::with_capacity(width as usize * height as usize);
+
+        for y in 0..height {
+            sampler.sample_row(y, &columns, &mut values);
+        }
+
+        Self { width, height, value, max_iterations: params.max_iterations, values, interior: Vec::new() }
+    }
+
+    /// A field of the given size with every value zero, for filling in
+    /// piecemeal.
+    pub fn blank(width: u16, height: u16, value: FieldValue, max_iterations: u32) -> Self {
+        let values = vec![0.0; width as usize * height as usize];
+        Self { width, height, value, max_iterations, values, interior: Vec::new() }
+    }
+
+    /// Sets a `size`-square block, clipped to the field, to one value and
+    /// optionally one interior shade.
+    pub fn fill_block(&mut self, left: u16, top: u16, size: u16, value: f64, shade: Option<f64>) {
+        let right = left.saturating_add(size).min(self.width);
+        let bottom = top.saturating_add(size).min(self.height);
+
+        for y in top..bottom {
+            let row = y as usize * self.width as usize;
+            self.values[row + left as usize..row + right as usize].fill(value);
+            if let Some(shade) = shade {
+                self.interior[row + left as usize..row + right as usize].fill(shade);
+            }
+        }
+    }
+
+    pub fn get(&self, x: u16, y: u16) -> f64 {
+        self.values[y as usize * self.width as usize + x as usize]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[f64]> {
+        self.values.chunks(self.width as usize)
+    }
+}
+
+/// Computes field values for chosen pixels of a view, through the
+/// vectorised kernel or the deep renderer where the view allows.
+pub struct Sampler<'a> {
+    params: &'a FractalParameters,
+    width: u16,
+    height: u16,
+    value: FieldValue,
+    pixel: f64,
+    /// Set for views too deep for f64, with the bailout the renderer
+    /// iterates to. Traps and root basins are not supported this deep.
+    deep: Option<(DeepRenderer, f64)>,
+}
+
+impl<'a> Sampler<'a> {
+    pub fn new(params: &'a FractalParameters, width: u16, height: u16, value: FieldValue) -> Self {
+        let deep = (is_deep(params) && matches!(value, FieldValue::Iterations | FieldValue::Smooth | FieldValue::Distance))
+            .then(|| {
+                let bailout = match value {
+                    FieldValue::Distance => DISTANCE_BAILOUT,
+                    _ => params.formula.bailout().unwrap_or(DISTANCE_BAILOUT),
+                };
+                (DeepRenderer::new(params, bailout), bailout)
+            });
+
+        Self { params, width, height, value, pixel: pixel_size(width, params), deep }
+    }
+
+    /// Appends the values of the pixels in row `y` at columns `xs`.
+    pub fn sample_row(&self, y: u16, xs: &[u16], values: &mut Vec<f64>) {
+        let params = self.params;
+
+        if let Some((renderer, bailout)) = &self.deep {
+            for &x in xs {
+                let orbit = renderer.iterate(x, y, self.width, self.height, params, *bailout);
+                values.push(match self.value {
+                    FieldValue::Smooth => smooth_value(orbit.iterations, orbit.z, params),
+                    FieldValue::Distance => distance_value(orbit.iterations, params.max_iterations, orbit.z, orbit.dz) / self.pixel,
+                    _ => orbit.iterations as f64,
+                });
+            }
+            return;
+        }
+
+        if matches!(self.value, FieldValue::Iterations | FieldValue::Smooth) && params.formula == Formula::Quadratic {
+            let mut points: Vec<_> = xs.iter().map(|&x| map_point_to_complex(x, y, self.width, self.height, params)).collect();
+            let mut counts = vec![0; xs.len()];
+            quadratic_iterations(&mut points, params.c, params.max_iterations, &mut counts);
+            values.extend(counts.iter().zip(&points).map(|(&count, &z)| match self.value {
+                FieldValue::Smooth => smooth_value(count, z, params),
+                _ => count as f64,
+            }));
+            return;
+        }
+
+        for &x in xs {
+            let z = map_point_to_complex(x, y, self.width, self.height, params);
+            values.push(match self.value {
+                FieldValue::Iterations => calculate_iterations(z, params) as f64,
+                FieldValue::Smooth => smooth_iterations(z, params),
+                FieldValue::Distance => distance_estimate(z, params) / self.pixel,
+                FieldValue::Trap => {
+                    let orbit = orbit_trap(z, params);
+                    if orbit.iterations == params.max_iterations { f64::INFINITY } else { orbit.distance }
+                }
+                FieldValue::Root => root_basin(z, params).map_or(f64::INFINITY, |basin| {
+                    basin.root as f64 + basin.iterations as f64 / params.max_iterations as f64
+                }),
+            });
+        }
+    }
+
+    /// Appends the interior shades of the pixels in row `y` at columns
+    /// `xs`, given their `values` from `sample_row`. Only points that
+    /// reached the iteration cap are iterated again; escaping points get NaN.
+    pub fn interior_row(&self, y: u16, xs: &[u16], values: &[f64], mode: InteriorMode, interior: &mut Vec<f64>) {
+        interior.extend(xs.iter().zip(values).map(|(&x, &value)| {
+            if !self.reached_cap(value) {
+                return f64::NAN;
+            }
+            let z = map_point_to_complex(x, y, self.width, self.height, self.params);
+            interior_value(z, self.params, mode).unwrap_or(f64::NAN)
+        }));
+    }
+
+    /// Whether a sampled value marks a point whose orbit never finished.
+    fn reached_cap(&self, value: f64) -> bool {
+        match self.value {
+            FieldValue::Iterations | FieldValue::Smooth => value >= self.params.max_iterations as f64,
+            FieldValue::Distance => value == 0.0,
+            FieldValue::Trap | FieldValue::Root => value.is_infinite(),
+        }
+    }
+}
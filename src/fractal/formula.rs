@@ -0,0 +1,351 @@
+use std::f64::consts::{FRAC_PI_2, TAU};
+use std::fmt;
+
+use num_complex::Complex;
+use rand::prelude::*;
+
+use super::precision::{BigComplex, DEEP_ZOOM, MAX_ZOOM};
+
+/// Convergence tolerance (squared step length) for root-finding formulas.
+const CONVERGENCE_TOLERANCE: f64 = 1e-12;
+/// Step used for numeric derivatives of formulas without a closed form.
+const DERIVATIVE_STEP: f64 = 1e-7;
+
+/// The families of fractals that can be selected, independent of their
+/// parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FractalKind {
+    Julia,
+    Multibrot,
+    BurningShip,
+    Tricorn,
+    Phoenix,
+    Newton,
+    Nova,
+    Magnet,
+}
+
+impl FractalKind {
+    pub fn next(&self) -> Self {
+        match self {
+            FractalKind::Julia => FractalKind::Multibrot,
+            FractalKind::Multibrot => FractalKind::BurningShip,
+            FractalKind::BurningShip => FractalKind::Tricorn,
+            FractalKind::Tricorn => FractalKind::Phoenix,
+            FractalKind::Phoenix => FractalKind::Newton,
+            FractalKind::Newton => FractalKind::Nova,
+            FractalKind::Nova => FractalKind::Magnet,
+            FractalKind::Magnet => FractalKind::Julia,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FractalKind::Julia => "Julia",
+            FractalKind::Multibrot => "Multibrot",
+            FractalKind::BurningShip => "Burning Ship",
+            FractalKind::Tricorn => "Tricorn",
+            FractalKind::Phoenix => "Phoenix",
+            FractalKind::Newton => "Newton",
+            FractalKind::Nova => "Nova",
+            FractalKind::Magnet => "Magnet",
+        }
+    }
+
+    /// Whether the kind colors by the root an orbit converges to rather
+    /// than by escape.
+    pub fn finds_roots(&self) -> bool {
+        matches!(self, FractalKind::Newton | FractalKind::Nova)
+    }
+}
+
+/// The iteration formula applied to each point, with any parameters beyond
+/// `c` that it needs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Formula {
+    /// z² + c
+    Quadratic,
+    /// zⁿ + c, for real n
+    Multibrot { power: f64 },
+    /// (|Re z| + i|Im z|)² + c
+    BurningShip,
+    /// conj(z)² + c
+    Tricorn,
+    /// z² + c + p·z₋₁, where z₋₁ is the previous orbit point
+    Phoenix { p: Complex<f64> },
+    /// Newton's method on the polynomial with the given roots; c is unused
+    Newton { roots: Vec<Complex<f64>> },
+    /// Relaxed Newton's method plus c: z − a·P(z)/P'(z) + c
+    Nova { roots: Vec<Complex<f64>>, relaxation: Complex<f64> },
+    /// Magnet type I: ((z² + c − 1) / (2z + c − 2))²
+    Magnet,
+}
+
+impl Formula {
+    pub fn kind(&self) -> FractalKind {
+        match self {
+            Formula::Quadratic => FractalKind::Julia,
+            Formula::Multibrot { .. } => FractalKind::Multibrot,
+            Formula::BurningShip => FractalKind::BurningShip,
+            Formula::Tricorn => FractalKind::Tricorn,
+            Formula::Phoenix { .. } => FractalKind::Phoenix,
+            Formula::Newton { .. } => FractalKind::Newton,
+            Formula::Nova { .. } => FractalKind::Nova,
+            Formula::Magnet => FractalKind::Magnet,
+        }
+    }
+
+    pub fn random(kind: FractalKind, rng: &mut impl Rng) -> Self {
+        match kind {
+            FractalKind::Julia => Formula::Quadratic,
+            FractalKind::Multibrot => Formula::Multibrot { power: rng.gen_range(2.0..6.0) },
+            FractalKind::BurningShip => Formula::BurningShip,
+            FractalKind::Tricorn => Formula::Tricorn,
+            FractalKind::Phoenix => Formula::Phoenix {
+                p: Complex::new(rng.gen_range(-0.6..-0.3), rng.gen_range(-0.1..0.1)),
+            },
+            FractalKind::Newton => Formula::Newton { roots: random_roots(rng) },
+            FractalKind::Nova => Formula::Nova {
+                roots: random_roots(rng),
+                relaxation: random_relaxation(rng),
+            },
+            FractalKind::Magnet => Formula::Magnet,
+        }
+    }
+
+    /// New values for the formula's own parameters, keeping its kind, so a
+    /// transition can morph between the two. Root-finding formulas keep
+    /// their polynomial's shape and rotate its roots.
+    pub fn vary(&self, rng: &mut impl Rng) -> Self {
+        match self {
+            Formula::Newton { roots } => Formula::Newton { roots: rotate_roots(roots, rng) },
+            Formula::Nova { roots, .. } => Formula::Nova {
+                roots: rotate_roots(roots, rng),
+                relaxation: random_relaxation(rng),
+            },
+            _ => Formula::random(self.kind(), rng),
+        }
+    }
+
+    pub fn roots(&self) -> Option<&[Complex<f64>]> {
+        match self {
+            Formula::Newton { roots } | Formula::Nova { roots, .. } => Some(roots),
+            _ => None,
+        }
+    }
+
+    /// Replaces the roots of a root-finding formula, leaving others as is.
+    pub fn set_roots(&mut self, new_roots: &[Complex<f64>]) {
+        if let Formula::Newton { roots } | Formula::Nova { roots, .. } = self {
+            *roots = new_roots.to_vec();
+        }
+    }
+
+    /// Replaces the roots with `anchor` turned by a random angle, as `vary`
+    /// does, without drifting from `anchor` over repeated transitions.
+    pub fn set_rotated_roots(&mut self, anchor: &[Complex<f64>], rng: &mut impl Rng) {
+        self.set_roots(&rotate_roots(anchor, rng));
+    }
+
+    /// Interpolates every numeric parameter. A quadratic is treated as a
+    /// multibrot of power 2, so the two can morph into each other; other
+    /// mismatched formulas switch over at the end.
+    pub fn lerp(&self, other: &Self, t: f64) -> Self {
+        let mix = |a: f64, b: f64| a * (1.0 - t) + b * t;
+
+        match (self, other) {
+            (Formula::Multibrot { power: a }, Formula::Multibrot { power: b }) => Formula::Multibrot { power: mix(*a, *b) },
+            (Formula::Quadratic, Formula::Multibrot { power }) => Formula::Multibrot { power: mix(2.0, *power) },
+            (Formula::Multibrot { power }, Formula::Quadratic) => Formula::Multibrot { power: mix(*power, 2.0) },
+            (Formula::Phoenix { p: a }, Formula::Phoenix { p: b }) => Formula::Phoenix { p: a * (1.0 - t) + b * t },
+            (Formula::Newton { roots: a }, Formula::Newton { roots: b }) if a.len() == b.len() => Formula::Newton {
+                roots: a.iter().zip(b).map(|(a, b)| a * (1.0 - t) + b * t).collect(),
+            },
+            (
+                Formula::Nova { roots: a, relaxation: ra },
+                Formula::Nova { roots: b, relaxation: rb },
+            ) if a.len() == b.len() => Formula::Nova {
+                roots: a.iter().zip(b).map(|(a, b)| a * (1.0 - t) + b * t).collect(),
+                relaxation: ra * (1.0 - t) + rb * t,
+            },
+            _ if t < 1.0 => self.clone(),
+            _ => other.clone(),
+        }
+    }
+
+    /// A `c` in the region where this formula's Julia sets are interesting.
+    pub fn random_c(&self, rng: &mut impl Rng) -> Complex<f64> {
+        match self {
+            Formula::Quadratic | Formula::Multibrot { .. } | Formula::Tricorn => {
+                Complex::new(rng.gen_range(-0.8..0.8), rng.gen_range(-0.8..0.8))
+            }
+            Formula::BurningShip => Complex::new(rng.gen_range(-1.2..0.2), rng.gen_range(-1.2..0.2)),
+            Formula::Phoenix { .. } => Complex::new(rng.gen_range(0.3..0.6), rng.gen_range(-0.1..0.1)),
+            Formula::Newton { .. } => Complex::new(0.0, 0.0),
+            Formula::Nova { .. } => Complex::new(rng.gen_range(-0.3..0.3), rng.gen_range(-0.3..0.3)),
+            Formula::Magnet => Complex::new(rng.gen_range(0.5..2.0), rng.gen_range(-1.0..1.0)),
+        }
+    }
+
+    /// Zoom range that frames this formula's sets.
+    pub fn zoom_range(&self) -> std::ops::Range<f64> {
+        match self {
+            Formula::Magnet => 0.15..0.4,
+            Formula::Newton { .. } | Formula::Nova { .. } => 0.4..1.0,
+            _ => 0.5..1.5,
+        }
+    }
+
+    pub fn step(&self, z: Complex<f64>, prev: Complex<f64>, c: Complex<f64>) -> Complex<f64> {
+        match self {
+            Formula::Quadratic => z * z + c,
+            Formula::Multibrot { power } => z.powf(*power) + c,
+            Formula::BurningShip => {
+                let folded = Complex::new(z.re.abs(), z.im.abs());
+                folded * folded + c
+            }
+            Formula::Tricorn => {
+                let conjugate = z.conj();
+                conjugate * conjugate + c
+            }
+            Formula::Phoenix { p } => z * z + c + p * prev,
+            Formula::Newton { roots } => z - newton_step(z, roots),
+            Formula::Nova { roots, relaxation } => z - relaxation * newton_step(z, roots) + c,
+            Formula::Magnet => {
+                let q = (z * z + c - 1.0) / (2.0 * z + c - 2.0);
+                q * q
+            }
+        }
+    }
+
+    /// `step` in arbitrary precision, for formulas built only from
+    /// squaring, folding and addition. Others return `None` and are never
+    /// iterated past f64's range.
+    pub fn step_big(&self, z: &BigComplex, prev: &BigComplex, c: &BigComplex) -> Option<BigComplex> {
+        match self {
+            Formula::Quadratic => Some(&z.square() + c),
+            Formula::BurningShip => Some(&z.abs().square() + c),
+            Formula::Tricorn => Some(&z.conj().square() + c),
+            Formula::Phoenix { p } => {
+                let p = BigComplex::from_complex(*p, z.re.bits());
+                Some(&(&z.square() + c) + &(&p * prev))
+            }
+            _ => None,
+        }
+    }
+
+    /// Perturbed step: given a reference point Z and a pixel's offset δ from
+    /// it, the offset after one step. Only defined for formulas whose
+    /// offsets can be iterated without the full point.
+    pub fn step_delta(&self, reference: Complex<f64>, delta: Complex<f64>) -> Complex<f64> {
+        match self {
+            Formula::Tricorn => ((2.0 * reference + delta) * delta).conj(),
+            _ => (2.0 * reference + delta) * delta,
+        }
+    }
+
+    /// Whether orbits can be checked for cycles on z alone. Root-finding
+    /// formulas finish by converging anyway, and Phoenix also depends on
+    /// the previous point.
+    pub fn detects_cycles(&self) -> bool {
+        self.bailout().is_some() && !matches!(self, Formula::Phoenix { .. })
+    }
+
+    pub fn supports_perturbation(&self) -> bool {
+        matches!(self, Formula::Quadratic | Formula::Tricorn)
+    }
+
+    pub fn supports_big(&self) -> bool {
+        matches!(self, Formula::Quadratic | Formula::BurningShip | Formula::Tricorn | Formula::Phoenix { .. })
+    }
+
+    /// Deepest zoom that still resolves pixels: the center's precision
+    /// limit with a big-number path, otherwise where f64 runs out.
+    pub fn max_zoom(&self) -> f64 {
+        if self.supports_big() { MAX_ZOOM } else { DEEP_ZOOM }
+    }
+
+    /// Derivative of `step` with respect to z. The folding formulas use 2z,
+    /// which has the right modulus, and the Phoenix term in z₋₁ is ignored.
+    pub fn derivative(&self, z: Complex<f64>, prev: Complex<f64>, c: Complex<f64>) -> Complex<f64> {
+        match self {
+            Formula::Quadratic | Formula::BurningShip | Formula::Tricorn | Formula::Phoenix { .. } => 2.0 * z,
+            Formula::Multibrot { power } => *power * z.powf(power - 1.0),
+            Formula::Newton { .. } | Formula::Nova { .. } | Formula::Magnet => {
+                let h = Complex::new(DERIVATIVE_STEP, 0.0);
+                (self.step(z + h, prev, c) - self.step(z - h, prev, c)) / (2.0 * DERIVATIVE_STEP)
+            }
+        }
+    }
+
+    /// Escape radius squared, or `None` for formulas that converge to roots
+    /// instead of escaping.
+    pub fn bailout(&self) -> Option<f64> {
+        match self {
+            Formula::Newton { .. } | Formula::Nova { .. } => None,
+            Formula::Magnet => Some(100.0),
+            _ => Some(4.0),
+        }
+    }
+
+    /// Whether an orbit has finished: escaped, or for root-finding formulas
+    /// converged.
+    pub fn finished(&self, z: Complex<f64>, prev: Complex<f64>) -> bool {
+        match self.bailout() {
+            Some(bailout) => z.norm_sqr() > bailout,
+            None => (z - prev).norm_sqr() < CONVERGENCE_TOLERANCE,
+        }
+    }
+
+    /// Degree of growth near infinity, used to smooth escape counts.
+    pub fn degree(&self) -> Option<f64> {
+        match self {
+            Formula::Multibrot { power } => Some(*power),
+            Formula::Newton { .. } | Formula::Nova { .. } => None,
+            _ => Some(2.0),
+        }
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Formula::Multibrot { power } => write!(f, "Multibrot (n = {:.3})", power),
+            Formula::Phoenix { p } => write!(f, "Phoenix (p = {:.3}{:+.3}i)", p.re, p.im),
+            Formula::Newton { roots } => write!(f, "Newton ({} roots)", roots.len()),
+            Formula::Nova { roots, relaxation } => {
+                write!(f, "Nova ({} roots, a = {:.3}{:+.3}i)", roots.len(), relaxation.re, relaxation.im)
+            }
+            _ => f.write_str(self.kind().name()),
+        }
+    }
+}
+
+/// P(z)/P'(z) for the monic polynomial with the given roots, using
+/// P'/P = Σ 1/(z − r).
+fn newton_step(z: Complex<f64>, roots: &[Complex<f64>]) -> Complex<f64> {
+    let ratio: Complex<f64> = roots.iter().map(|r| (z - r).inv()).sum();
+    ratio.inv()
+}
+
+fn random_roots(rng: &mut impl Rng) -> Vec<Complex<f64>> {
+    let count = rng.gen_range(3..=5);
+    let turn = rng.gen_range(0.0..TAU);
+    (0..count)
+        .map(|i| {
+            let angle = turn + TAU * i as f64 / count as f64 + rng.gen_range(-0.3..0.3);
+            Complex::from_polar(rng.gen_range(0.7..1.3), angle)
+        })
+        .collect()
+}
+
+fn random_relaxation(rng: &mut impl Rng) -> Complex<f64> {
+    Complex::new(rng.gen_range(0.8..1.2), rng.gen_range(-0.2..0.2))
+}
+
+/// Rotates the roots about their centroid by up to a quarter turn.
+fn rotate_roots(roots: &[Complex<f64>], rng: &mut impl Rng) -> Vec<Complex<f64>> {
+    let centroid = roots.iter().sum::<Complex<f64>>() / roots.len().max(1) as f64;
+    let rotation = Complex::from_polar(1.0, rng.gen_range(-FRAC_PI_2..FRAC_PI_2));
+    roots.iter().map(|r| centroid + (r - centroid) * rotation).collect()
+}
@@ -0,0 +1,99 @@
+use std::f64::consts::TAU;
+
+use num_complex::Complex;
+
+use super::julia::Orbit;
+use super::parameters::FractalParameters;
+
+/// Longest attracting cycle looked for by period detection.
+const MAX_PERIOD: u32 = 64;
+const PERIOD_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InteriorMode {
+    Flat,
+    FinalModulus,
+    Period,
+    MeanAngle,
+    Multiplier,
+}
+
+impl InteriorMode {
+    pub fn next(&self) -> Self {
+        match self {
+            InteriorMode::Flat => InteriorMode::FinalModulus,
+            InteriorMode::FinalModulus => InteriorMode::Period,
+            InteriorMode::Period => InteriorMode::MeanAngle,
+            InteriorMode::MeanAngle => InteriorMode::Multiplier,
+            InteriorMode::Multiplier => InteriorMode::Flat,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InteriorMode::Flat => "Flat",
+            InteriorMode::FinalModulus => "Final |z|",
+            InteriorMode::Period => "Period",
+            InteriorMode::MeanAngle => "Mean angle",
+            InteriorMode::Multiplier => "Cycle multiplier",
+        }
+    }
+}
+
+/// Shade in `[0, 1]` for a point whose orbit never escapes, or `None` if
+/// it escapes or the mode is flat.
+pub fn interior_value(z: Complex<f64>, params: &FractalParameters, mode: InteriorMode) -> Option<f64> {
+    if mode == InteriorMode::Flat {
+        return None;
+    }
+
+    let mut orbit = Orbit::new(z, params);
+    let mut heading = Complex::new(0.0, 0.0);
+
+    for _ in 0..params.max_iterations {
+        if orbit.finished() {
+            return None;
+        }
+        orbit.step();
+        if orbit.z.norm_sqr() > 0.0 {
+            heading += orbit.z / orbit.z.norm();
+        }
+    }
+
+    let z = orbit.z;
+
+    match mode {
+        InteriorMode::Flat => None,
+        InteriorMode::FinalModulus => Some((z.norm() / 2.0).min(1.0)),
+        InteriorMode::MeanAngle => Some(heading.arg() / TAU + 0.5),
+        InteriorMode::Period => {
+            // Spread successive periods around the palette with the golden
+            // ratio so neighboring periods get distinct colors
+            let period = find_cycle(&orbit).map_or(0, |(period, _)| period);
+            Some((period as f64 * 0.618_033_988_75).fract())
+        }
+        InteriorMode::Multiplier => {
+            let multiplier = find_cycle(&orbit).map_or(1.0, |(_, multiplier)| multiplier.norm());
+            Some(multiplier.min(1.0))
+        }
+    }
+}
+
+/// Looks for the cycle the orbit has settled on, starting from its current
+/// point. Returns its period and multiplier, the derivative of the cycle
+/// map.
+fn find_cycle(orbit: &Orbit) -> Option<(u32, Complex<f64>)> {
+    let start = orbit.z;
+    let mut orbit = orbit.clone();
+    let mut multiplier = Complex::new(1.0, 0.0);
+
+    for period in 1..=MAX_PERIOD {
+        multiplier *= orbit.derivative();
+        orbit.step();
+        if (orbit.z - start).norm_sqr() < PERIOD_TOLERANCE * PERIOD_TOLERANCE {
+            return Some((period, multiplier));
+        }
+    }
+
+    None
+}
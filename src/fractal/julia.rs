@@ -1,27 +1,242 @@
 use num_complex::Complex;
+use super::formula::Formula;
 use super::parameters::FractalParameters;
 
-pub const MAX_ITERATIONS: u32 = 100;
+/// Iteration cap for new views. The frame scheduler lowers it while frames
+/// run slow.
+pub const DEFAULT_MAX_ITERATIONS: u32 = 100;
 
-pub fn calculate_iterations(z: Complex<f64>, c: Complex<f64>) -> u32 {
-    let mut z = z;
+/// Escape radius squared for distance estimation. A large radius keeps the
+/// estimate accurate near the boundary.
+pub const DISTANCE_BAILOUT: f64 = 1e6;
+
+/// Squared distance within which an orbit counts as having returned to a
+/// point it visited before.
+pub const PERIOD_TOLERANCE: f64 = 1e-16;
+
+/// An orbit under the active formula. Keeps the previous point for
+/// formulas, like Phoenix, that depend on it.
+#[derive(Clone)]
+pub struct Orbit<'a> {
+    pub z: Complex<f64>,
+    pub prev: Complex<f64>,
+    formula: &'a Formula,
+    c: Complex<f64>,
+}
+
+impl<'a> Orbit<'a> {
+    pub fn new(z: Complex<f64>, params: &'a FractalParameters) -> Self {
+        Self {
+            z,
+            prev: Complex::new(0.0, 0.0),
+            formula: &params.formula,
+            c: params.c,
+        }
+    }
+
+    pub fn step(&mut self) {
+        let next = self.formula.step(self.z, self.prev, self.c);
+        self.prev = self.z;
+        self.z = next;
+    }
+
+    pub fn derivative(&self) -> Complex<f64> {
+        self.formula.derivative(self.z, self.prev, self.c)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.formula.finished(self.z, self.prev)
+    }
+}
+
+/// Brent-style cycle detection. The orbit is compared against a saved point
+/// that is refreshed at iterations 1, 2, 4, 8, ..., so a cycle of any period
+/// is caught within a few multiples of it. A periodic orbit never escapes,
+/// so it can be stopped early instead of running to the iteration cap.
+pub struct CycleCheck {
+    saved: Complex<f64>,
+    checkpoint: u32,
+}
+
+impl CycleCheck {
+    pub fn new(z: Complex<f64>) -> Self {
+        Self { saved: z, checkpoint: 1 }
+    }
+
+    /// Whether `z`, reached after `i` steps, repeats the saved point.
+    pub fn is_cycle(&mut self, z: Complex<f64>, i: u32) -> bool {
+        if (z - self.saved).norm_sqr() < PERIOD_TOLERANCE {
+            return true;
+        }
+        if i == self.checkpoint {
+            self.saved = z;
+            self.checkpoint *= 2;
+        }
+        false
+    }
+}
+
+/// Runs an orbit until it finishes, returning the number of steps taken,
+/// or the iteration cap if it never finishes.
+fn run_orbit(orbit: &mut Orbit, params: &FractalParameters) -> u32 {
+    let mut cycle = params.formula.detects_cycles().then(|| CycleCheck::new(orbit.z));
     let mut i = 0;
 
-    while i < MAX_ITERATIONS && z.norm_sqr() <= 4.0 {
-        z = z * z + c;
+    while i < params.max_iterations && !orbit.finished() {
+        orbit.step();
         i += 1;
+        if let Some(cycle) = &mut cycle
+            && cycle.is_cycle(orbit.z, i) {
+            return params.max_iterations;
+        }
     }
 
     i
 }
 
-pub fn map_point_to_complex(x: u16, y: u16, width: u16, height: u16, params: &FractalParameters) -> Complex<f64> {
+pub fn calculate_iterations(z: Complex<f64>, params: &FractalParameters) -> u32 {
+    run_orbit(&mut Orbit::new(z, params), params)
+}
+
+/// Continuous (normalized) escape count. The integer part matches
+/// `calculate_iterations`, and points that never escape return
+/// the iteration cap exactly.
+pub fn smooth_iterations(z: Complex<f64>, params: &FractalParameters) -> f64 {
+    let mut orbit = Orbit::new(z, params);
+    let i = run_orbit(&mut orbit, params);
+    smooth_value(i, orbit.z, params)
+}
+
+/// Smooth escape count from the iteration count and final point of an
+/// orbit.
+pub fn smooth_value(i: u32, z: Complex<f64>, params: &FractalParameters) -> f64 {
+    if i == params.max_iterations {
+        return params.max_iterations as f64;
+    }
+
+    let (Some(degree), Some(bailout)) = (params.formula.degree(), params.formula.bailout()) else {
+        return i as f64;
+    };
+
+    let escape = z.norm().ln() / (bailout.ln() / 2.0);
+    let fraction = 1.0 - escape.ln() / degree.ln();
+    i as f64 + fraction.clamp(0.0, 0.999)
+}
+
+/// Exterior distance estimate from `z` to the Julia set, tracking the
+/// derivative alongside the orbit. Points that never escape are treated as
+/// lying on the set and return `0.0`. Root-finding formulas have no
+/// exterior, so every point is reported as far away.
+pub fn distance_estimate(z: Complex<f64>, params: &FractalParameters) -> f64 {
+    if params.formula.bailout().is_none() {
+        return f64::MAX;
+    }
+
+    let mut orbit = Orbit::new(z, params);
+    let mut cycle = params.formula.detects_cycles().then(|| CycleCheck::new(z));
+    let mut dz = Complex::new(1.0, 0.0);
+    let mut i = 0;
+
+    while i < params.max_iterations && orbit.z.norm_sqr() <= DISTANCE_BAILOUT {
+        dz *= orbit.derivative();
+        orbit.step();
+        i += 1;
+        if let Some(cycle) = &mut cycle
+            && cycle.is_cycle(orbit.z, i) {
+            return 0.0;
+        }
+    }
+
+    distance_value(i, params.max_iterations, orbit.z, dz)
+}
+
+/// Distance estimate from the iteration count, final point and derivative
+/// of an orbit run to `DISTANCE_BAILOUT`.
+pub fn distance_value(i: u32, max_iterations: u32, z: Complex<f64>, dz: Complex<f64>) -> f64 {
+    if i == max_iterations {
+        return 0.0;
+    }
+
+    let modulus = z.norm();
+    let derivative = dz.norm();
+    if derivative == 0.0 {
+        return f64::MAX;
+    }
+
+    modulus * modulus.ln() / derivative
+}
+
+pub struct TrapOrbit {
+    pub iterations: u32,
+    pub distance: f64,
+}
+
+/// Iterates like `calculate_iterations` while recording the closest
+/// approach of the orbit to the parameters' trap.
+pub fn orbit_trap(z: Complex<f64>, params: &FractalParameters) -> TrapOrbit {
+    let mut orbit = Orbit::new(z, params);
+    let mut i = 0;
+    let mut distance = params.trap.distance(z);
+
+    while i < params.max_iterations && !orbit.finished() {
+        orbit.step();
+        i += 1;
+        distance = distance.min(params.trap.distance(orbit.z));
+    }
+
+    TrapOrbit { iterations: i, distance }
+}
+
+pub struct Basin {
+    pub root: usize,
+    pub iterations: u32,
+}
+
+/// For root-finding formulas, the index of the root the orbit converges
+/// to and how quickly. `None` if it does not converge or the formula has
+/// no roots.
+pub fn root_basin(z: Complex<f64>, params: &FractalParameters) -> Option<Basin> {
+    let roots = params.formula.roots()?;
+    let mut orbit = Orbit::new(z, params);
+    let mut i = 0;
+
+    while i < params.max_iterations && !orbit.finished() {
+        orbit.step();
+        i += 1;
+    }
+
+    if i == params.max_iterations {
+        return None;
+    }
+
+    let root = roots
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| (orbit.z - *a).norm_sqr().total_cmp(&(orbit.z - *b).norm_sqr()))
+        .map(|(index, _)| index)?;
+
+    Some(Basin { root, iterations: i })
+}
+
+/// Width of one pixel in the complex plane.
+pub fn pixel_size(width: u16, params: &FractalParameters) -> f64 {
+    3.0 / params.zoom / width as f64
+}
+
+/// Position of a pixel relative to the center of the view.
+pub fn pixel_offset(x: u16, y: u16, width: u16, height: u16, params: &FractalParameters) -> Complex<f64> {
     let aspect_ratio = height as f64 / width as f64;
-    let real = (x as f64 / width as f64 - 0.5) * 3.0 / params.zoom + params.x_offset;
-    let imag = (y as f64 / height as f64 - 0.5) * 3.0 * aspect_ratio / params.zoom + params.y_offset;
+    let real = (x as f64 / width as f64 - 0.5) * 3.0 / params.zoom;
+    let imag = (y as f64 / height as f64 - 0.5) * 3.0 * aspect_ratio / params.zoom;
     Complex::new(real, imag)
 }
 
+/// Maps a pixel to the plane in f64. Past `DEEP_ZOOM` neighboring pixels
+/// collapse onto the same value; `deep::DeepView` maps those views instead.
+pub fn map_point_to_complex(x: u16, y: u16, width: u16, height: u16, params: &FractalParameters) -> Complex<f64> {
+    params.center() + pixel_offset(x, y, width, height, params)
+}
+
 pub fn measure_complexity(params: &FractalParameters, width: u16, height: u16) -> f64 {
     let sample_size = 100;
     let mut iteration_counts = Vec::with_capacity(sample_size);
@@ -31,7 +246,7 @@ pub fn measure_complexity(params: &FractalParameters, width: u16, height: u16) -
         let y = (i / 10) as u16 * (height / 10).max(1);
         
         let z = map_point_to_complex(x, y, width, height, params);
-        let iterations = calculate_iterations(z, params.c);
+        let iterations = calculate_iterations(z, params);
         iteration_counts.push(iterations);
     }
     
@@ -54,5 +269,86 @@ pub fn measure_complexity(params: &FractalParameters, width: u16, height: u16) -
     
     let std_dev = variance.sqrt();
     
-    (std_dev / (MAX_ITERATIONS as f64 / 2.0)).min(1.0)
+    (std_dev / (params.max_iterations as f64 / 2.0)).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::fractal::formula::FractalKind;
+
+    /// Connected sets that are mostly interior: the basilica and the
+    /// Douady rabbit.
+    fn interior_heavy() -> Vec<FractalParameters> {
+        [Complex::new(-1.0, 0.0), Complex::new(-0.123, 0.745)]
+            .into_iter()
+            .map(|c| {
+                let mut params = FractalParameters::random(FractalKind::Julia);
+                params.formula = Formula::Quadratic;
+                params.c = c;
+                params.zoom = 1.0;
+                params.pan(-params.center());
+                params
+            })
+            .collect()
+    }
+
+    /// The plain loop, without cycle detection.
+    fn unchecked_iterations(z: Complex<f64>, params: &FractalParameters) -> u32 {
+        let mut orbit = Orbit::new(z, params);
+        let mut i = 0;
+        while i < params.max_iterations && !orbit.finished() {
+            orbit.step();
+            i += 1;
+        }
+        i
+    }
+
+    fn grid(params: &FractalParameters, width: u16, height: u16) -> Vec<Complex<f64>> {
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| map_point_to_complex(x, y, width, height, params))
+            .collect()
+    }
+
+    #[test]
+    fn cycle_detection_preserves_counts() {
+        for params in interior_heavy() {
+            for z in grid(&params, 160, 90) {
+                assert_eq!(calculate_iterations(z, &params), unchecked_iterations(z, &params), "z = {}", z);
+            }
+        }
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_cycle_detection() {
+        for mut params in interior_heavy() {
+            for max_iterations in [DEFAULT_MAX_ITERATIONS, 1000] {
+                params.max_iterations = max_iterations;
+                let points = grid(&params, 640, 360);
+
+                let start = Instant::now();
+                let unchecked: u64 = points.iter().map(|&z| unchecked_iterations(z, &params) as u64).sum();
+                let plain = start.elapsed();
+
+                let start = Instant::now();
+                let checked: u64 = points.iter().map(|&z| calculate_iterations(z, &params) as u64).sum();
+                let detected = start.elapsed();
+
+                assert_eq!(checked, unchecked);
+                println!(
+                    "c = {}, cap {}: {:?} without cycle detection, {:?} with ({:.1}x)",
+                    params.c,
+                    max_iterations,
+                    plain,
+                    detected,
+                    plain.as_secs_f64() / detected.as_secs_f64()
+                );
+            }
+        }
+    }
 }
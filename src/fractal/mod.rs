@@ -1,2 +1,13 @@
+pub mod deep;
+pub mod detail;
+pub mod field;
+pub mod formula;
+pub mod interior;
 pub mod julia;
 pub mod parameters;
+pub mod perturbation;
+pub mod polynomial;
+pub mod precision;
+pub mod progressive;
+pub mod simd;
+pub mod trap;
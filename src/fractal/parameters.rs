@@ -1,38 +1,90 @@
 use num_complex::Complex;
 use rand::prelude::*;
 
+use super::formula::{FractalKind, Formula};
+use super::julia::DEFAULT_MAX_ITERATIONS;
+use super::precision::{BigComplex, BigFloat, CENTER_BITS};
+use super::trap::{OrbitTrap, TrapShape};
+
 #[derive(Clone)]
 pub struct FractalParameters {
+    pub formula: Formula,
     pub c: Complex<f64>,
     pub zoom: f64,
-    pub x_offset: f64,
-    pub y_offset: f64,
+    /// Center of the view, kept in high precision so deep zooms stay sharp
+    pub x_offset: BigFloat,
+    pub y_offset: BigFloat,
+    /// Iterations before a point counts as part of the set
+    pub max_iterations: u32,
+    pub trap: OrbitTrap,
 }
 
 impl FractalParameters {
-    pub fn random() -> Self {
+    pub fn random(kind: FractalKind) -> Self {
+        let mut rng = rand::thread_rng();
+        let formula = Formula::random(kind, &mut rng);
+        Self::random_with(formula)
+    }
+
+    /// Random parameters of the same kind that a transition can morph into,
+    /// including new values for the formula's own parameters.
+    pub fn successor(&self) -> Self {
+        let formula = self.formula.vary(&mut rand::thread_rng());
+        let mut next = Self::random_with(formula);
+        next.trap.shape = self.trap.shape.clone();
+        // A view zoomed in past the random range was chosen by the user, so
+        // the next transition keeps it instead of jumping back out
+        if self.zoom > self.formula.zoom_range().end {
+            next.zoom = self.zoom;
+            next.x_offset = self.x_offset.clone();
+            next.y_offset = self.y_offset.clone();
+        }
+        next
+    }
+
+    /// Random view and `c` for a fixed formula.
+    pub fn random_with(formula: Formula) -> Self {
         let mut rng = rand::thread_rng();
         
         Self {
-            c: Complex::new(
-                rng.gen_range(-0.8..0.8),
-                rng.gen_range(-0.8..0.8),
-            ),
-            zoom: rng.gen_range(0.5..1.5),
-            x_offset: rng.gen_range(-0.5..0.5),
-            y_offset: rng.gen_range(-0.5..0.5),
+            c: formula.random_c(&mut rng),
+            zoom: rng.gen_range(formula.zoom_range()),
+            x_offset: BigFloat::from_f64(rng.gen_range(-0.5..0.5), CENTER_BITS),
+            y_offset: BigFloat::from_f64(rng.gen_range(-0.5..0.5), CENTER_BITS),
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            trap: OrbitTrap::random(TrapShape::Point),
+            formula,
         }
     }
     
+    /// Center of the view rounded to f64.
+    pub fn center(&self) -> Complex<f64> {
+        Complex::new(self.x_offset.to_f64(), self.y_offset.to_f64())
+    }
+
+    pub fn big_center(&self, bits: u32) -> BigComplex {
+        BigComplex::new(self.x_offset.with_precision(bits), self.y_offset.with_precision(bits))
+    }
+
+    /// Moves the center by `delta`, which is small at deep zooms and so
+    /// is added in full precision.
+    pub fn pan(&mut self, delta: Complex<f64>) {
+        self.x_offset = &self.x_offset + &BigFloat::from_f64(delta.re, CENTER_BITS);
+        self.y_offset = &self.y_offset + &BigFloat::from_f64(delta.im, CENTER_BITS);
+    }
+
     pub fn lerp(&self, other: &Self, t: f64) -> Self {
         Self {
+            formula: self.formula.lerp(&other.formula, t),
             c: Complex::new(
                 self.c.re * (1.0 - t) + other.c.re * t,
                 self.c.im * (1.0 - t) + other.c.im * t,
             ),
             zoom: self.zoom * (1.0 - t) + other.zoom * t,
-            x_offset: self.x_offset * (1.0 - t) + other.x_offset * t,
-            y_offset: self.y_offset * (1.0 - t) + other.y_offset * t,
+            x_offset: self.x_offset.lerp(&other.x_offset, t),
+            y_offset: self.y_offset.lerp(&other.y_offset, t),
+            max_iterations: self.max_iterations,
+            trap: self.trap.lerp(&other.trap, t),
         }
     }
 }
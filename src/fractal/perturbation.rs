@@ -0,0 +1,147 @@
+use num_complex::Complex;
+
+use super::deep::DeepOrbit;
+use super::parameters::FractalParameters;
+use super::precision::{BigComplex, precision_for_zoom};
+
+/// An orbit computed once in high precision and stored rounded to f64, so
+/// pixels can be iterated as small offsets from it.
+struct ReferenceOrbit {
+    points: Vec<Complex<f64>>,
+}
+
+impl ReferenceOrbit {
+    fn compute(start: BigComplex, c: &BigComplex, params: &FractalParameters, bailout: f64) -> Self {
+        let max_iterations = params.max_iterations as usize;
+        let mut points = Vec::with_capacity(max_iterations + 1);
+        let mut z = start;
+        let mut prev = BigComplex::from_complex(Complex::new(0.0, 0.0), z.re.bits());
+
+        loop {
+            let small = z.to_complex();
+            points.push(small);
+            if points.len() > max_iterations || small.norm_sqr() > bailout {
+                break;
+            }
+
+            let Some(next) = params.formula.step_big(&z, &prev, c) else {
+                break;
+            };
+            prev = z;
+            z = next;
+        }
+
+        Self { points }
+    }
+}
+
+/// Perturbation renderer for deep views: one reference orbit through the
+/// center in high precision, then every pixel as an f64 offset δ from it,
+/// stepped by δ' = 2Zδ + δ².
+///
+/// A pixel whose orbit passes closer to zero than to the reference has lost
+/// its precision (a glitch), and one that outlives the reference has
+/// nothing left to follow. Both are rebased onto the critical orbit, which
+/// starts at zero, by taking the full point as the new offset.
+pub struct Perturbation {
+    center: ReferenceOrbit,
+    critical: ReferenceOrbit,
+    bailout: f64,
+}
+
+impl Perturbation {
+    pub fn new(params: &FractalParameters, bailout: f64) -> Self {
+        let bits = precision_for_zoom(params.zoom);
+        let c = BigComplex::from_complex(params.c, bits);
+        let zero = BigComplex::from_complex(Complex::new(0.0, 0.0), bits);
+
+        Self {
+            center: ReferenceOrbit::compute(params.big_center(bits), &c, params, bailout),
+            critical: ReferenceOrbit::compute(zero, &c, params, bailout),
+            bailout,
+        }
+    }
+
+    /// Iterates the pixel at `offset` from the center of the view.
+    pub fn iterate(&self, offset: Complex<f64>, params: &FractalParameters) -> DeepOrbit {
+        let formula = &params.formula;
+        let zero = Complex::new(0.0, 0.0);
+        let mut reference = &self.center.points;
+        let mut n = 0;
+        let mut delta = offset;
+        let mut dz = Complex::new(1.0, 0.0);
+        let mut z = reference[0] + delta;
+        let mut i = 0;
+
+        while i < params.max_iterations && z.norm_sqr() <= self.bailout {
+            dz *= formula.derivative(z, zero, params.c);
+
+            if z.norm_sqr() < delta.norm_sqr() || n + 1 == reference.len() {
+                reference = &self.critical.points;
+                n = 0;
+                delta = z;
+            }
+
+            if n + 1 < reference.len() {
+                delta = formula.step_delta(reference[n], delta);
+                n += 1;
+            } else {
+                // The critical orbit escaped at once, so there is nothing to
+                // perturb from and the point is stepped directly
+                delta = formula.step(z, zero, params.c);
+                n = 0;
+            }
+
+            z = reference[n] + delta;
+            i += 1;
+        }
+
+        DeepOrbit { iterations: i, z, dz }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractal::deep::DeepView;
+    use crate::fractal::formula::{FractalKind, Formula};
+    use crate::fractal::julia::pixel_offset;
+    use crate::fractal::precision::{BigFloat, CENTER_BITS};
+
+    #[test]
+    fn matches_direct_iteration() {
+        // c = i makes the critical point pre-periodic, so zero lies on the
+        // dendrite and a deep view around it crosses plenty of boundary
+        let mut params = FractalParameters::random(FractalKind::Julia);
+        params.formula = Formula::Quadratic;
+        params.c = Complex::new(0.0, 1.0);
+        params.zoom = 1e20;
+        params.x_offset = BigFloat::from_f64(0.0, CENTER_BITS);
+        params.y_offset = BigFloat::from_f64(0.0, CENTER_BITS);
+        params.max_iterations = 400;
+
+        let bailout = 4.0;
+        let perturbation = Perturbation::new(&params, bailout);
+        let direct = DeepView::new(&params);
+        let (width, height) = (40, 20);
+
+        let mut counts = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let perturbed = perturbation.iterate(pixel_offset(x, y, width, height, &params), &params);
+                let exact = direct.iterate(direct.map_point(x, y, width, height, &params), &params, bailout);
+                assert_eq!(perturbed.iterations, exact.iterations, "pixel {}, {}", x, y);
+                // Rounding differences grow along the orbit, but stay far
+                // below what smooth coloring can show
+                if exact.iterations < params.max_iterations {
+                    assert!((perturbed.z - exact.z).norm() < 1e-3 * exact.z.norm(), "pixel {}, {}: {} != {}", x, y, perturbed.z, exact.z);
+                }
+                counts.push(exact.iterations);
+            }
+        }
+
+        counts.sort();
+        counts.dedup();
+        assert!(counts.len() > 10, "view too flat to test: {:?}", counts);
+    }
+}
@@ -0,0 +1,145 @@
+use num_complex::Complex;
+
+const MAX_ROOT_ITERATIONS: u32 = 500;
+const ROOT_TOLERANCE: f64 = 1e-12;
+
+/// Highest power accepted, which bounds the coefficient storage and the
+/// cost of root finding and of every Newton step.
+pub const MAX_DEGREE: usize = 32;
+
+/// Parses a polynomial in `z` with real coefficients, such as
+/// `z^3 - 2z + 2` or `0.5z^4 + z`. Returns coefficients indexed by power.
+pub fn parse(text: &str) -> Result<Vec<f64>, String> {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.is_empty() {
+        return Err("polynomial is empty".to_string());
+    }
+
+    let mut coefficients = Vec::new();
+    let mut rest = compact.as_str();
+
+    while !rest.is_empty() {
+        let sign = match rest.as_bytes()[0] {
+            b'-' => -1.0,
+            _ => 1.0,
+        };
+        rest = rest.strip_prefix(['+', '-']).unwrap_or(rest);
+
+        // A term runs to the next sign after its first character, which
+        // may be multi-byte in invalid input. Signs of a number's exponent,
+        // as in `1e-3z`, belong to the term.
+        let start = rest.char_indices().nth(1).map_or(rest.len(), |(i, _)| i);
+        let end = rest[start..]
+            .char_indices()
+            .find(|&(i, c)| matches!(c, '+' | '-') && !rest[..start + i].ends_with(['e', 'E']))
+            .map_or(rest.len(), |(i, _)| i + start);
+        let (term, remainder) = rest.split_at(end);
+        rest = remainder;
+
+        let (coefficient, power) = parse_term(term).ok_or_else(|| format!("invalid term '{}'", term))?;
+        if power > MAX_DEGREE {
+            return Err(format!("power {} is above the maximum of {}", power, MAX_DEGREE));
+        }
+        if coefficients.len() <= power {
+            coefficients.resize(power + 1, 0.0);
+        }
+        coefficients[power] += sign * coefficient;
+    }
+
+    if coefficients.iter().any(|c| !c.is_finite()) {
+        return Err("coefficients must be finite".to_string());
+    }
+    while coefficients.len() > 1 && coefficients.last() == Some(&0.0) {
+        coefficients.pop();
+    }
+    if coefficients.len() < 2 {
+        return Err("polynomial must have degree 1 or more".to_string());
+    }
+
+    Ok(coefficients)
+}
+
+/// Parses an unsigned term such as `3`, `z`, `2.5z`, `1.5*z^2` or `z^4`
+/// into a coefficient and power.
+fn parse_term(term: &str) -> Option<(f64, usize)> {
+    let Some(index) = term.find('z') else {
+        return Some((term.parse().ok().filter(|c: &f64| c.is_finite())?, 0));
+    };
+
+    let coefficient = term[..index].trim_end_matches('*');
+    let coefficient = if coefficient.is_empty() { 1.0 } else { coefficient.parse().ok().filter(|c: &f64| c.is_finite())? };
+
+    let power = match &term[index + 1..] {
+        "" => 1,
+        exponent => exponent.strip_prefix('^')?.parse().ok()?,
+    };
+
+    Some((coefficient, power))
+}
+
+/// Finds all complex roots of the polynomial with the Durand–Kerner method.
+pub fn roots(coefficients: &[f64]) -> Vec<Complex<f64>> {
+    let degree = coefficients.len() - 1;
+    let leading = coefficients[degree];
+    let monic: Vec<f64> = coefficients.iter().map(|c| c / leading).collect();
+
+    let evaluate = |z: Complex<f64>| monic.iter().rev().fold(Complex::new(0.0, 0.0), |acc, &c| acc * z + c);
+
+    // Standard starting points: powers of a number that is neither real
+    // nor a root of unity
+    let seed = Complex::new(0.4, 0.9);
+    let mut roots: Vec<Complex<f64>> = (0..degree).map(|i| seed.powu(i as u32)).collect();
+
+    for _ in 0..MAX_ROOT_ITERATIONS {
+        let mut change: f64 = 0.0;
+
+        for i in 0..degree {
+            let denominator: Complex<f64> = (0..degree)
+                .filter(|&j| j != i)
+                .map(|j| roots[i] - roots[j])
+                .product();
+            let delta = evaluate(roots[i]) / denominator;
+            roots[i] -= delta;
+            change = change.max(delta.norm_sqr());
+        }
+
+        if change < ROOT_TOLERANCE {
+            break;
+        }
+    }
+
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_terms() {
+        assert_eq!(parse("z^3 - 2z + 2"), Ok(vec![2.0, -2.0, 0.0, 1.0]));
+        assert_eq!(parse("0.5z^4 + z"), Ok(vec![0.0, 1.0, 0.0, 0.0, 0.5]));
+        assert_eq!(parse("-1.5*z^2 + 3"), Ok(vec![3.0, 0.0, -1.5]));
+        assert_eq!(parse("1e-3z^2 - 1"), Ok(vec![-1.0, 0.0, 1e-3]));
+        assert_eq!(parse("2E+1z - 1e2"), Ok(vec![-100.0, 20.0]));
+        assert_eq!(parse("z^2 + 1 - z^2 + z"), Ok(vec![1.0, 1.0]));
+    }
+
+    #[test]
+    fn rejects_invalid_polynomials() {
+        for text in ["", "3", "z^2 - z^2", "nan z^2 + 1", "inf z", "z^2 + nan", "1e308z + 1e308z", "z^33", "2y + 1", "z² + 1", "é-z"] {
+            assert!(parse(text).is_err(), "{:?}", text);
+        }
+        assert!(parse("z^32 + 1").is_ok());
+    }
+
+    #[test]
+    fn finds_roots() {
+        let mut found = roots(&parse("z^3 - 1").unwrap());
+        found.sort_by(|a, b| a.im.total_cmp(&b.im));
+        let expected = [Complex::from_polar(1.0, -2.0 * std::f64::consts::PI / 3.0), Complex::new(1.0, 0.0), Complex::from_polar(1.0, 2.0 * std::f64::consts::PI / 3.0)];
+        for (root, expected) in found.iter().zip(expected) {
+            assert!((root - expected).norm() < 1e-9, "{} != {}", root, expected);
+        }
+    }
+}
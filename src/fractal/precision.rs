@@ -0,0 +1,254 @@
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num_bigint::BigInt;
+use num_complex::Complex;
+use num_traits::{Float, Signed, ToPrimitive, Zero};
+
+/// Fractional bits kept for view centers, enough for zooms far beyond what
+/// can be iterated at interactive speed.
+pub const CENTER_BITS: u32 = 512;
+
+/// Zoom past which f64 can no longer tell neighboring pixels apart.
+pub const DEEP_ZOOM: f64 = 1e13;
+
+/// Deepest zoom a center of `CENTER_BITS` can follow, 2^(CENTER_BITS - 64)
+/// or about 7e134. That leaves 64 bits below the view width for pixels and
+/// pans, which would otherwise round away.
+pub const MAX_ZOOM: f64 = f64::from_bits((1023 + CENTER_BITS as u64 - 64) << 52);
+
+/// Fractional bits needed to resolve pixels at `zoom`, with headroom for
+/// the error that builds up over an orbit.
+pub fn precision_for_zoom(zoom: f64) -> u32 {
+    let needed = 64 + zoom.clamp(1.0, MAX_ZOOM).log2().ceil() as u32;
+    needed.div_ceil(32) * 32
+}
+
+/// Binary fixed-point number: `mantissa / 2^bits`. Operands of different
+/// precision are aligned to the finer one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BigFloat {
+    mantissa: BigInt,
+    bits: u32,
+}
+
+impl BigFloat {
+    pub fn zero(bits: u32) -> Self {
+        Self { mantissa: BigInt::zero(), bits }
+    }
+
+    pub fn from_f64(value: f64, bits: u32) -> Self {
+        if value == 0.0 || !value.is_finite() {
+            return Self::zero(bits);
+        }
+
+        let (mantissa, exponent, sign) = value.integer_decode();
+        let shift = exponent as i64 + bits as i64;
+        let mut scaled = BigInt::from(mantissa);
+        scaled = if shift >= 0 { scaled << shift as u64 } else { scaled >> (-shift) as u64 };
+        if sign < 0 {
+            scaled = -scaled;
+        }
+
+        Self { mantissa: scaled, bits }
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        // Drop low bits first so the conversion never overflows
+        let excess = self.mantissa.bits().saturating_sub(64);
+        let top = (&self.mantissa >> excess).to_f64().unwrap_or(0.0);
+        top * 2f64.powi(excess as i32 - self.bits as i32)
+    }
+
+    pub fn with_precision(&self, bits: u32) -> Self {
+        let mantissa = if bits >= self.bits {
+            &self.mantissa << (bits - self.bits)
+        } else {
+            &self.mantissa >> (self.bits - bits)
+        };
+        Self { mantissa, bits }
+    }
+
+    pub fn abs(&self) -> Self {
+        Self { mantissa: self.mantissa.abs(), bits: self.bits }
+    }
+
+    pub fn lerp(&self, other: &Self, t: f64) -> Self {
+        let bits = self.bits.max(other.bits);
+        self + &(&(other - self) * &BigFloat::from_f64(t, bits))
+    }
+
+    fn aligned(a: &Self, b: &Self) -> (BigInt, BigInt, u32) {
+        let bits = a.bits.max(b.bits);
+        (a.with_precision(bits).mantissa, b.with_precision(bits).mantissa, bits)
+    }
+}
+
+impl Add for &BigFloat {
+    type Output = BigFloat;
+
+    fn add(self, other: &BigFloat) -> BigFloat {
+        let (a, b, bits) = BigFloat::aligned(self, other);
+        BigFloat { mantissa: a + b, bits }
+    }
+}
+
+impl Sub for &BigFloat {
+    type Output = BigFloat;
+
+    fn sub(self, other: &BigFloat) -> BigFloat {
+        let (a, b, bits) = BigFloat::aligned(self, other);
+        BigFloat { mantissa: a - b, bits }
+    }
+}
+
+impl Mul for &BigFloat {
+    type Output = BigFloat;
+
+    fn mul(self, other: &BigFloat) -> BigFloat {
+        let (a, b, bits) = BigFloat::aligned(self, other);
+        BigFloat { mantissa: (a * b) >> bits, bits }
+    }
+}
+
+impl Neg for &BigFloat {
+    type Output = BigFloat;
+
+    fn neg(self) -> BigFloat {
+        BigFloat { mantissa: -&self.mantissa, bits: self.bits }
+    }
+}
+
+/// Prints as many decimal digits as the precision supports, or at most as
+/// many as a format precision asks for, rounded half away from zero.
+impl fmt::Display for BigFloat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let supported = (self.bits as f64 * std::f64::consts::LOG10_2).ceil() as usize;
+        let digits = f.precision().map_or(supported, |precision| precision.min(supported));
+        let mut scaled = self.mantissa.abs() * BigInt::from(10).pow(digits as u32);
+        if self.bits > 0 {
+            scaled += BigInt::from(1) << (self.bits - 1);
+        }
+        let scaled = scaled >> self.bits;
+        let text = format!("{:0>width$}", scaled.to_string(), width = digits + 1);
+        let (whole, fraction) = text.split_at(text.len() - digits);
+        // Values that round to zero print without a sign
+        let sign = if self.mantissa.is_negative() && !scaled.is_zero() { "-" } else { "" };
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            write!(f, "{}{}", sign, whole)
+        } else {
+            write!(f, "{}{}.{}", sign, whole, fraction)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BigComplex {
+    pub re: BigFloat,
+    pub im: BigFloat,
+}
+
+impl BigComplex {
+    pub fn new(re: BigFloat, im: BigFloat) -> Self {
+        Self { re, im }
+    }
+
+    pub fn from_complex(z: Complex<f64>, bits: u32) -> Self {
+        Self::new(BigFloat::from_f64(z.re, bits), BigFloat::from_f64(z.im, bits))
+    }
+
+    pub fn to_complex(&self) -> Complex<f64> {
+        Complex::new(self.re.to_f64(), self.im.to_f64())
+    }
+
+    pub fn with_precision(&self, bits: u32) -> Self {
+        Self::new(self.re.with_precision(bits), self.im.with_precision(bits))
+    }
+
+    pub fn square(&self) -> Self {
+        let re = &(&self.re * &self.re) - &(&self.im * &self.im);
+        let cross = &self.re * &self.im;
+        Self::new(re, &cross + &cross)
+    }
+
+    pub fn abs(&self) -> Self {
+        Self::new(self.re.abs(), self.im.abs())
+    }
+
+    pub fn conj(&self) -> Self {
+        Self::new(self.re.clone(), -&self.im)
+    }
+}
+
+impl Add for &BigComplex {
+    type Output = BigComplex;
+
+    fn add(self, other: &BigComplex) -> BigComplex {
+        BigComplex::new(&self.re + &other.re, &self.im + &other.im)
+    }
+}
+
+impl Mul for &BigComplex {
+    type Output = BigComplex;
+
+    fn mul(self, other: &BigComplex) -> BigComplex {
+        let re = &(&self.re * &other.re) - &(&self.im * &other.im);
+        let im = &(&self.re * &other.im) + &(&self.im * &other.re);
+        BigComplex::new(re, im)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_rounded_to_precision() {
+        let format = |value: f64, precision: usize| format!("{:.*}", precision, BigFloat::from_f64(value, CENTER_BITS));
+        assert_eq!(format(-0.3, 6), "-0.3");
+        assert_eq!(format(0.7, 7), "0.7");
+        assert_eq!(format(1.25, 1), "1.3");
+        assert_eq!(format(-1.25, 1), "-1.3");
+        assert_eq!(format(0.9999999, 3), "1");
+        assert_eq!(format(-1e-9, 6), "0");
+        assert_eq!(format(0.0, 6), "0");
+        assert_eq!(format!("{}", BigFloat::from_f64(0.5, 8)), "0.5");
+        assert_eq!(format!("{}", BigFloat::from_f64(-2.0, 8)), "-2");
+    }
+
+    #[test]
+    fn arithmetic_matches_f64() {
+        let values = [0.0, 1.0, -0.3, 0.7, 1.5e-8, -2.75, 123.456];
+        for &a in &values {
+            for &b in &values {
+                let (x, y) = (BigFloat::from_f64(a, CENTER_BITS), BigFloat::from_f64(b, 256));
+                assert_eq!((&x + &y).to_f64(), a + b, "{} + {}", a, b);
+                assert_eq!((&x - &y).to_f64(), a - b, "{} - {}", a, b);
+                assert!(((&x * &y).to_f64() - a * b).abs() <= (a * b).abs() * f64::EPSILON, "{} * {}", a, b);
+                assert_eq!((&x + &y).bits(), CENTER_BITS);
+            }
+        }
+
+        // Precision beyond f64 survives arithmetic
+        let tiny = BigFloat::from_f64(2f64.powi(-300), CENTER_BITS);
+        let one = BigFloat::from_f64(1.0, CENTER_BITS);
+        assert_eq!((&(&one + &tiny) - &one).to_f64(), 2f64.powi(-300));
+    }
+
+    #[test]
+    fn round_trips_f64() {
+        for value in [0.0, 1.0, -0.3, 0.1, 1e-100, -1e100, 2f64.powi(-400), f64::MAX] {
+            assert_eq!(BigFloat::from_f64(value, CENTER_BITS).to_f64(), value, "{}", value);
+        }
+        assert_eq!(BigFloat::from_f64(f64::NAN, CENTER_BITS).to_f64(), 0.0);
+
+        let z = Complex::new(-0.75, 0.1);
+        assert_eq!(BigComplex::from_complex(z, CENTER_BITS).to_complex(), z);
+        assert_eq!(BigComplex::from_complex(z, CENTER_BITS).square().to_complex(), z * z);
+    }
+}
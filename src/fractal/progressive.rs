@@ -0,0 +1,89 @@
+use super::field::{FieldValue, IterationField, Sampler};
+use super::interior::InteriorMode;
+use super::parameters::FractalParameters;
+
+/// Block sizes of the passes, coarsest first.
+pub const PASSES: [u16; 4] = [8, 4, 2, 1];
+
+/// A field computed in passes of decreasing block size. Every pass fills
+/// whole blocks, so the field can be drawn after any of them. Passes after
+/// the first can be interrupted between rows and pick up where they left
+/// off. The field is complete after the pass with block size `finest`, so a
+/// larger value trades resolution for speed.
+pub struct ProgressiveField {
+    params: FractalParameters,
+    interior_mode: InteriorMode,
+    field: IterationField,
+    passes: usize,
+    pass: usize,
+    row: u16,
+}
+
+impl ProgressiveField {
+    pub fn new(params: FractalParameters, width: u16, height: u16, value: FieldValue, interior_mode: InteriorMode, finest: u16) -> Self {
+        let mut field = IterationField::blank(width, height, value, params.max_iterations);
+        if interior_mode != InteriorMode::Flat {
+            field.interior = vec![f64::NAN; field.values.len()];
+        }
+        let passes = PASSES.iter().position(|&step| step <= finest).map_or(PASSES.len(), |last| last + 1);
+
+        Self { params, interior_mode, field, passes, pass: 0, row: 0 }
+    }
+
+    pub fn field(&self) -> &IterationField {
+        &self.field
+    }
+
+    pub fn into_field(self) -> IterationField {
+        self.field
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.pass == self.passes
+    }
+
+    /// Runs the current pass to its end, checking `interrupted` before each
+    /// row. Returns `false` if it stopped early.
+    pub fn refine(&mut self, mut interrupted: impl FnMut() -> bool) -> bool {
+        if self.is_complete() {
+            return true;
+        }
+        let step = PASSES[self.pass];
+
+        let (width, height) = (self.field.width, self.field.height);
+        let sampler = Sampler::new(&self.params, width, height, self.field.value);
+        let mut values = Vec::with_capacity(width as usize);
+        let mut interior = Vec::with_capacity(width as usize);
+
+        while self.row < height {
+            if self.pass > 0 && interrupted() {
+                return false;
+            }
+
+            let y = self.row;
+            // Points on the coarser grid were computed by an earlier pass
+            let coarser = step * 2;
+            let xs: Vec<u16> = (0..width)
+                .step_by(step as usize)
+                .filter(|x| self.pass == 0 || !y.is_multiple_of(coarser) || !x.is_multiple_of(coarser))
+                .collect();
+
+            values.clear();
+            sampler.sample_row(y, &xs, &mut values);
+            interior.clear();
+            if self.interior_mode != InteriorMode::Flat {
+                sampler.interior_row(y, &xs, &values, self.interior_mode, &mut interior);
+            }
+
+            for (i, &x) in xs.iter().enumerate() {
+                self.field.fill_block(x, y, step, values[i], interior.get(i).copied());
+            }
+
+            self.row += step;
+        }
+
+        self.pass += 1;
+        self.row = 0;
+        true
+    }
+}
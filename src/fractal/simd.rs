@@ -0,0 +1,268 @@
+use num_complex::Complex;
+
+use super::julia::{CycleCheck, PERIOD_TOLERANCE};
+
+/// Escape radius squared, matching `Formula::Quadratic`.
+const BAILOUT: f64 = 4.0;
+
+/// Escape counts for z² + c over a batch of starting points, several points
+/// per instruction where the CPU supports it. Each point is left at the end
+/// of its orbit, for smooth coloring. Each lane performs the same
+/// operations in the same order as `calculate_iterations`, including its
+/// cycle check and without fused multiply-adds, so the counts and escaped
+/// points are identical.
+pub fn quadratic_iterations(points: &mut [Complex<f64>], c: Complex<f64>, max_iterations: u32, counts: &mut [u32]) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx") {
+            // SAFETY: AVX support was just checked
+            unsafe { x86::quadratic_avx(points, c, max_iterations, counts) };
+            return;
+        }
+        if is_x86_feature_detected!("sse2") {
+            // SAFETY: SSE2 support was just checked
+            unsafe { x86::quadratic_sse2(points, c, max_iterations, counts) };
+            return;
+        }
+    }
+
+    quadratic_scalar(points, c, max_iterations, counts);
+}
+
+pub fn quadratic_scalar(points: &mut [Complex<f64>], c: Complex<f64>, max_iterations: u32, counts: &mut [u32]) {
+    for (z, count) in points.iter_mut().zip(counts.iter_mut()) {
+        *count = scalar_iterations(z, c, max_iterations);
+    }
+}
+
+fn scalar_iterations(z: &mut Complex<f64>, c: Complex<f64>, max_iterations: u32) -> u32 {
+    let mut cycle = CycleCheck::new(*z);
+    let mut i = 0;
+    while i < max_iterations {
+        if z.norm_sqr() > BAILOUT {
+            break;
+        }
+        *z = *z * *z + c;
+        i += 1;
+        if cycle.is_cycle(*z, i) {
+            return max_iterations;
+        }
+    }
+    i
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    use num_complex::Complex;
+
+    use super::{BAILOUT, PERIOD_TOLERANCE, scalar_iterations};
+
+    /// Four points per iteration. Lanes that have escaped or cycled stop
+    /// counting and keep their last value, and the loop ends once every
+    /// lane has. Cycled lanes are given the full count at the end.
+    #[target_feature(enable = "avx")]
+    pub fn quadratic_avx(points: &mut [Complex<f64>], c: Complex<f64>, max_iterations: u32, counts: &mut [u32]) {
+        let chunks = points.len() / 4 * 4;
+        let c_re = _mm256_set1_pd(c.re);
+        let c_im = _mm256_set1_pd(c.im);
+        let bailout = _mm256_set1_pd(BAILOUT);
+        let tolerance = _mm256_set1_pd(PERIOD_TOLERANCE);
+        let one = _mm256_set1_pd(1.0);
+        let max = _mm256_set1_pd(max_iterations as f64);
+
+        for start in (0..chunks).step_by(4) {
+            let p = &points[start..start + 4];
+            let mut re = _mm256_set_pd(p[3].re, p[2].re, p[1].re, p[0].re);
+            let mut im = _mm256_set_pd(p[3].im, p[2].im, p[1].im, p[0].im);
+            let mut count = _mm256_setzero_pd();
+            let (mut saved_re, mut saved_im) = (re, im);
+            let mut checkpoint = 1;
+            let mut cycled = _mm256_setzero_pd();
+
+            for i in 1..=max_iterations {
+                let norm = _mm256_add_pd(_mm256_mul_pd(re, re), _mm256_mul_pd(im, im));
+                // Not-greater-than keeps NaN lanes running, as the scalar loop does
+                let active = _mm256_andnot_pd(cycled, _mm256_cmp_pd::<_CMP_NGT_UQ>(norm, bailout));
+                if _mm256_movemask_pd(active) == 0 {
+                    break;
+                }
+                count = _mm256_add_pd(count, _mm256_and_pd(active, one));
+
+                let next_re = _mm256_add_pd(_mm256_sub_pd(_mm256_mul_pd(re, re), _mm256_mul_pd(im, im)), c_re);
+                let next_im = _mm256_add_pd(_mm256_add_pd(_mm256_mul_pd(re, im), _mm256_mul_pd(im, re)), c_im);
+                re = _mm256_blendv_pd(re, next_re, active);
+                im = _mm256_blendv_pd(im, next_im, active);
+
+                let d_re = _mm256_sub_pd(re, saved_re);
+                let d_im = _mm256_sub_pd(im, saved_im);
+                let distance = _mm256_add_pd(_mm256_mul_pd(d_re, d_re), _mm256_mul_pd(d_im, d_im));
+                cycled = _mm256_or_pd(cycled, _mm256_and_pd(active, _mm256_cmp_pd::<_CMP_LT_OQ>(distance, tolerance)));
+                if i == checkpoint {
+                    (saved_re, saved_im) = (re, im);
+                    checkpoint *= 2;
+                }
+            }
+            count = _mm256_blendv_pd(count, max, cycled);
+
+            let (mut lanes, mut lanes_re, mut lanes_im) = ([0.0; 4], [0.0; 4], [0.0; 4]);
+            // SAFETY: each array holds exactly four f64s
+            unsafe {
+                _mm256_storeu_pd(lanes.as_mut_ptr(), count);
+                _mm256_storeu_pd(lanes_re.as_mut_ptr(), re);
+                _mm256_storeu_pd(lanes_im.as_mut_ptr(), im);
+            }
+            for lane in 0..4 {
+                counts[start + lane] = lanes[lane] as u32;
+                points[start + lane] = Complex::new(lanes_re[lane], lanes_im[lane]);
+            }
+        }
+
+        for i in chunks..points.len() {
+            counts[i] = scalar_iterations(&mut points[i], c, max_iterations);
+        }
+    }
+
+    /// Two points per iteration; SSE2 has no blend, so lanes are selected
+    /// with and/andnot/or.
+    #[target_feature(enable = "sse2")]
+    pub fn quadratic_sse2(points: &mut [Complex<f64>], c: Complex<f64>, max_iterations: u32, counts: &mut [u32]) {
+        let chunks = points.len() / 2 * 2;
+        let c_re = _mm_set1_pd(c.re);
+        let c_im = _mm_set1_pd(c.im);
+        let bailout = _mm_set1_pd(BAILOUT);
+        let tolerance = _mm_set1_pd(PERIOD_TOLERANCE);
+        let one = _mm_set1_pd(1.0);
+        let max = _mm_set1_pd(max_iterations as f64);
+
+        for start in (0..chunks).step_by(2) {
+            let p = &points[start..start + 2];
+            let mut re = _mm_set_pd(p[1].re, p[0].re);
+            let mut im = _mm_set_pd(p[1].im, p[0].im);
+            let mut count = _mm_setzero_pd();
+            let (mut saved_re, mut saved_im) = (re, im);
+            let mut checkpoint = 1;
+            let mut cycled = _mm_setzero_pd();
+
+            for i in 1..=max_iterations {
+                let norm = _mm_add_pd(_mm_mul_pd(re, re), _mm_mul_pd(im, im));
+                let active = _mm_andnot_pd(cycled, _mm_cmpngt_pd(norm, bailout));
+                if _mm_movemask_pd(active) == 0 {
+                    break;
+                }
+                count = _mm_add_pd(count, _mm_and_pd(active, one));
+
+                let next_re = _mm_add_pd(_mm_sub_pd(_mm_mul_pd(re, re), _mm_mul_pd(im, im)), c_re);
+                let next_im = _mm_add_pd(_mm_add_pd(_mm_mul_pd(re, im), _mm_mul_pd(im, re)), c_im);
+                re = _mm_or_pd(_mm_and_pd(active, next_re), _mm_andnot_pd(active, re));
+                im = _mm_or_pd(_mm_and_pd(active, next_im), _mm_andnot_pd(active, im));
+
+                let d_re = _mm_sub_pd(re, saved_re);
+                let d_im = _mm_sub_pd(im, saved_im);
+                let distance = _mm_add_pd(_mm_mul_pd(d_re, d_re), _mm_mul_pd(d_im, d_im));
+                cycled = _mm_or_pd(cycled, _mm_and_pd(active, _mm_cmplt_pd(distance, tolerance)));
+                if i == checkpoint {
+                    (saved_re, saved_im) = (re, im);
+                    checkpoint *= 2;
+                }
+            }
+            count = _mm_or_pd(_mm_and_pd(cycled, max), _mm_andnot_pd(cycled, count));
+
+            let (mut lanes, mut lanes_re, mut lanes_im) = ([0.0; 2], [0.0; 2], [0.0; 2]);
+            // SAFETY: each array holds exactly two f64s
+            unsafe {
+                _mm_storeu_pd(lanes.as_mut_ptr(), count);
+                _mm_storeu_pd(lanes_re.as_mut_ptr(), re);
+                _mm_storeu_pd(lanes_im.as_mut_ptr(), im);
+            }
+            for lane in 0..2 {
+                counts[start + lane] = lanes[lane] as u32;
+                points[start + lane] = Complex::new(lanes_re[lane], lanes_im[lane]);
+            }
+        }
+
+        for i in chunks..points.len() {
+            counts[i] = scalar_iterations(&mut points[i], c, max_iterations);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractal::formula::{FractalKind, Formula};
+    use crate::fractal::julia::{DEFAULT_MAX_ITERATIONS, calculate_iterations, smooth_iterations, smooth_value};
+    use crate::fractal::parameters::FractalParameters;
+
+    /// A grid over the whole escape region plus awkward values: exact
+    /// bailout hits, huge and non-finite starts.
+    fn sample_points() -> Vec<Complex<f64>> {
+        let mut points: Vec<_> = (0..61)
+            .flat_map(|y| (0..83).map(move |x| Complex::new(x as f64 / 41.0 - 1.0, y as f64 / 30.0 - 1.0) * 2.0))
+            .collect();
+        points.extend([
+            Complex::new(2.0, 0.0),
+            Complex::new(0.0, -2.0),
+            Complex::new(1e300, 1e300),
+            Complex::new(f64::NAN, 0.0),
+            Complex::new(f64::INFINITY, 0.0),
+        ]);
+        points
+    }
+
+    fn quadratic_params(c: Complex<f64>, max_iterations: u32) -> FractalParameters {
+        let mut params = FractalParameters::random(FractalKind::Julia);
+        params.formula = Formula::Quadratic;
+        params.c = c;
+        params.max_iterations = max_iterations;
+        params
+    }
+
+    /// Smooth values as bits, so NaN compares equal to itself.
+    fn smooth_bits(counts: &[u32], finals: &[Complex<f64>], params: &FractalParameters) -> Vec<u64> {
+        counts.iter().zip(finals).map(|(&count, &z)| smooth_value(count, z, params).to_bits()).collect()
+    }
+
+    #[test]
+    fn kernels_match_scalar_iterations() {
+        let points = sample_points();
+        let constants = [Complex::new(-0.8, 0.156), Complex::new(0.285, 0.01), Complex::new(-0.4, 0.6), Complex::new(-2.0, 0.0)];
+        for (c, max) in constants.into_iter().flat_map(|c| [37, DEFAULT_MAX_ITERATIONS, 500].map(|max| (c, max))) {
+            let params = quadratic_params(c, max);
+            let expected: Vec<_> = points.iter().map(|z| calculate_iterations(*z, &params)).collect();
+            let expected_smooth: Vec<_> = points.iter().map(|z| smooth_iterations(*z, &params).to_bits()).collect();
+            let mut counts = vec![0; points.len()];
+
+            let mut finals = points.clone();
+            quadratic_scalar(&mut finals, c, max, &mut counts);
+            assert_eq!(counts, expected, "scalar, c = {}, max = {}", c, max);
+            assert_eq!(smooth_bits(&counts, &finals, &params), expected_smooth, "scalar smooth, c = {}, max = {}", c, max);
+
+            let mut finals = points.clone();
+            quadratic_iterations(&mut finals, c, max, &mut counts);
+            assert_eq!(counts, expected, "dispatched, c = {}, max = {}", c, max);
+            assert_eq!(smooth_bits(&counts, &finals, &params), expected_smooth, "dispatched smooth, c = {}, max = {}", c, max);
+
+            #[cfg(target_arch = "x86_64")]
+            {
+                if is_x86_feature_detected!("avx") {
+                    counts.fill(0);
+                    let mut finals = points.clone();
+                    // SAFETY: AVX support was just checked
+                    unsafe { x86::quadratic_avx(&mut finals, c, max, &mut counts) };
+                    assert_eq!(counts, expected, "avx, c = {}, max = {}", c, max);
+                    assert_eq!(smooth_bits(&counts, &finals, &params), expected_smooth, "avx smooth, c = {}, max = {}", c, max);
+                }
+                if is_x86_feature_detected!("sse2") {
+                    counts.fill(0);
+                    let mut finals = points.clone();
+                    // SAFETY: SSE2 support was just checked
+                    unsafe { x86::quadratic_sse2(&mut finals, c, max, &mut counts) };
+                    assert_eq!(counts, expected, "sse2, c = {}, max = {}", c, max);
+                    assert_eq!(smooth_bits(&counts, &finals, &params), expected_smooth, "sse2 smooth, c = {}, max = {}", c, max);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::f64::consts::PI;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use num_complex::Complex;
+use rand::prelude::*;
+
+/// Built-in glyph used until one is supplied, drawn with `#` cells.
+const DEFAULT_GLYPH: &str = "\
+..###..
+.#...#.
+#.....#
+#..#..#
+#.....#
+.#...#.
+..###..";
+
+/// A shape drawn with filled cells, scaled to fit the unit square.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glyph {
+    points: Vec<Complex<f64>>,
+}
+
+impl Glyph {
+    /// Parses ASCII art where `#` marks a filled cell and anything else is
+    /// empty. The art is centered on the origin, longest side spanning 2.
+    pub fn parse(art: &str) -> Option<Self> {
+        let rows: Vec<&str> = art.lines().collect();
+        let height = rows.len();
+        let width = rows.iter().map(|row| row.chars().count()).max()?;
+        let scale = 2.0 / width.max(height) as f64;
+
+        let points: Vec<_> = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.chars().enumerate().filter(|&(_, ch)| ch == '#').map(move |(x, _)| {
+                    Complex::new(
+                        (x as f64 + 0.5 - width as f64 / 2.0) * scale,
+                        (y as f64 + 0.5 - height as f64 / 2.0) * scale,
+                    )
+                })
+            })
+            .collect();
+
+        (!points.is_empty()).then_some(Self { points })
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let art = fs::read_to_string(path)?;
+        Self::parse(&art).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: glyph has no '#' cells", path.display()))
+        })
+    }
+
+    fn distance(&self, z: Complex<f64>) -> f64 {
+        self.points.iter().map(|p| (z - p).norm_sqr()).fold(f64::MAX, f64::min).sqrt()
+    }
+}
+
+impl Default for Glyph {
+    fn default() -> Self {
+        Self::parse(DEFAULT_GLYPH).expect("default glyph has filled cells")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrapShape {
+    Point,
+    Line,
+    Cross,
+    Circle,
+    Glyph(Arc<Glyph>),
+}
+
+impl TrapShape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TrapShape::Point => "Point",
+            TrapShape::Line => "Line",
+            TrapShape::Cross => "Cross",
+            TrapShape::Circle => "Circle",
+            TrapShape::Glyph(_) => "Glyph",
+        }
+    }
+}
+
+/// Trap placement. The shape is chosen by the user, while the numeric
+/// fields are randomized and interpolated with the rest of the parameters,
+/// except for a center pinned with `--trap-position`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitTrap {
+    pub shape: TrapShape,
+    pub center: Complex<f64>,
+    pub angle: f64,
+    pub radius: f64,
+}
+
+impl OrbitTrap {
+    pub fn random(shape: TrapShape) -> Self {
+        let mut rng = rand::thread_rng();
+
+        Self {
+            shape,
+            center: Complex::new(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5)),
+            angle: rng.gen_range(0.0..PI),
+            radius: rng.gen_range(0.2..0.8),
+        }
+    }
+
+    pub fn lerp(&self, other: &Self, t: f64) -> Self {
+        Self {
+            shape: self.shape.clone(),
+            center: self.center * (1.0 - t) + other.center * t,
+            angle: self.angle * (1.0 - t) + other.angle * t,
+            radius: self.radius * (1.0 - t) + other.radius * t,
+        }
+    }
+
+    /// Distance from `z` to the trap shape.
+    pub fn distance(&self, z: Complex<f64>) -> f64 {
+        // Work in the trap's frame: centered and rotated so the line is real
+        let local = (z - self.center) * Complex::from_polar(1.0, -self.angle);
+
+        match &self.shape {
+            TrapShape::Point => local.norm(),
+            TrapShape::Line => local.im.abs(),
+            TrapShape::Cross => local.re.abs().min(local.im.abs()),
+            TrapShape::Circle => (local.norm() - self.radius).abs(),
+            TrapShape::Glyph(glyph) => glyph.distance(local / self.radius) * self.radius,
+        }
+    }
+}
@@ -1,22 +1,76 @@
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use crossterm::event::{self, Event};
 use std::io;
-use std::thread;
-use std::time::Duration;
+use std::sync::Arc;
 
 mod app;
+mod export;
 mod fractal;
 mod ui;
 mod utils;
 
+use app::config::Config;
+use app::keymap::{Action, KeyBinding, Keymap};
+use app::scheduler::FrameScheduler;
 use app::state::AppState;
+use fractal::formula::FractalKind;
+use fractal::field::IterationField;
+use fractal::polynomial;
+use fractal::trap::Glyph;
 use fractal::julia::measure_complexity;
-use ui::terminal::{setup_terminal, cleanup_terminal, display_help};
-use ui::renderer::render_fractal;
+use ui::terminal::{setup_terminal, cleanup_terminal, display_help, display_status};
+use ui::colors::{ColorMode, ColorScheme};
+use ui::hud::{Hud, display_hud};
+use ui::palette::cosine::CosinePalette;
+use ui::palette::load_palettes;
+use ui::render_thread::{ExportKind, ExportRequest, RenderRequest, RenderThread};
+use ui::renderer::draw_field;
+use utils::{PAN_STEP, ZOOM_STEP};
+use utils::log;
 
 fn main() -> io::Result<()> {
+    let config = Config::from_args()?;
+    if let Some(level) = config.log_level {
+        log::init(level, config.log_file.as_deref())?;
+    }
+    let trap_glyph = config.trap_glyph.as_deref().map(Glyph::load).transpose()?;
+    let keymap = match &config.keymap {
+        Some(path) => Keymap::load(path)?,
+        None => Keymap::default(),
+    };
     let screen_size = setup_terminal()?;
     
     let mut app_state = AppState::new();
+    if let Some(glyph) = trap_glyph {
+        app_state.trap_glyph = Arc::new(glyph);
+    }
+    if let Some(center) = config.trap_position {
+        app_state.set_trap_center(center);
+    }
+    if let Some(coefficients) = &config.polynomial {
+        app_state.polynomial_roots = Some(polynomial::roots(coefficients));
+        app_state.set_fractal_kind(FractalKind::Newton);
+        app_state.color_mode = ColorMode::RootBasin;
+    }
+
+    let (palettes, palette_errors) = load_palettes(&config.palette_dir);
+    app_state.add_palettes(palettes);
+    if let Some(seed) = config.palette_seed {
+        app_state.seed_palettes(seed);
+    }
+    for err in &palette_errors {
+        log_warn!("{}", err);
+    }
+    if let Some(err) = palette_errors.first() {
+        app_state.set_status(format!("Palette error ({} total): {}", palette_errors.len(), err));
+    }
+
+    let conflicts = keymap.conflicts();
+    for conflict in &conflicts {
+        log_warn!("Key conflict: {}", conflict);
+    }
+    if let Some(conflict) = conflicts.first() {
+        app_state.set_status(format!("Key conflict ({} total): {}", conflicts.len(), conflict));
+    }
     
     let initial_complexity = measure_complexity(
         &app_state.current_fractal, 
@@ -25,57 +79,200 @@ fn main() -> io::Result<()> {
     );
     app_state.update_transition_time(initial_complexity);
 
+    let mut render_thread = RenderThread::spawn();
+    let mut scheduler = FrameScheduler::new(config.target_fps, config.max_iterations);
+    let mut frame: Option<IterationField> = None;
+
     loop {
-        if event::poll(Duration::from_millis(10))? {
-            if let Event::Key(KeyEvent { code, .. }) = event::read()? {
-                match code {
-                    KeyCode::Char('q') => break,
-                    KeyCode::Char('c') => {
-                        app_state.next_color_scheme();
-                    }
-                    KeyCode::Char('n') => {
-                        let complexity = measure_complexity(
-                            &app_state.next_fractal, 
-                            screen_size.width, 
-                            screen_size.height
-                        );
-                        app_state.start_new_transition(complexity);
-                    }
-                    KeyCode::Char('h') => {
-                        app_state.toggle_help();
-                        if !app_state.show_help {
-                            render_fractal(
-                                &app_state.current_fractal,
-                                app_state.color_scheme, 
-                                screen_size.width, 
-                                screen_size.height
-                            )?;
-                        }
-                    }
-                    _ => {
-                        if app_state.show_help {
-                            app_state.show_help = false;
-                            render_fractal(
-                                &app_state.current_fractal,
-                                app_state.color_scheme, 
-                                screen_size.width, 
-                                screen_size.height
-                            )?;
-                        }
+        // Waiting on input until the next frame is due both paces the loop
+        // and answers keys without delay
+        if event::poll(scheduler.remaining())?
+            && let Event::Key(key) = event::read()? {
+            let action = keymap.action(KeyBinding::from(key));
+
+            // Any key but quit closes the help panel, the next frame drawing
+            // over it
+            if app_state.show_help && action != Some(Action::Quit) {
+                app_state.show_help = false;
+                continue;
+            }
+
+            match action {
+                Some(Action::Quit) => break,
+                Some(Action::NextScheme) => {
+                    app_state.next_color_scheme();
+                    let message = match app_state.color_scheme() {
+                        ColorScheme::Cosine(palette) => describe_cosine(palette),
+                        scheme => format!("Color scheme: {}", scheme.name()),
+                    };
+                    app_state.set_status(message);
+                }
+                Some(Action::NewCosinePalette) => {
+                    let palette = app_state.new_cosine_palette();
+                    app_state.set_status(describe_cosine(&palette));
+                }
+                Some(Action::SavePalette) => {
+                    let message = match app_state.color_scheme() {
+                        ColorScheme::Cosine(palette) => match palette.save(&config.palette_dir) {
+                            Ok(path) => format!("Saved {}", path.display()),
+                            Err(err) => format!("Save failed: {}", err),
+                        },
+                        _ => "Only cosine palettes can be saved".to_string(),
+                    };
+                    app_state.set_status(message);
+                }
+                Some(Action::NewSet) => {
+                    let complexity = measure_complexity(
+                        &app_state.next_fractal, 
+                        screen_size.width, 
+                        screen_size.height
+                    );
+                    app_state.start_new_transition(complexity);
+                    render_thread.invalidate();
+                }
+                Some(Action::NextFractal) => {
+                    let kind = app_state.current_fractal.formula.kind().next();
+                    app_state.set_fractal_kind(kind);
+                    if kind.finds_roots() {
+                        app_state.color_mode = ColorMode::RootBasin;
+                    } else if app_state.color_mode == ColorMode::RootBasin {
+                        app_state.color_mode = ColorMode::EscapeTime;
                     }
+                    let complexity = measure_complexity(
+                        &app_state.current_fractal,
+                        screen_size.width,
+                        screen_size.height
+                    );
+                    app_state.update_transition_time(complexity);
+                    app_state.set_status(format!("Fractal: {}", app_state.current_fractal.formula));
+                    render_thread.invalidate();
+                }
+                Some(action @ (Action::ExportField | Action::ExportContours)) => {
+                    let elapsed = app_state.transition_elapsed();
+                    let mut view = app_state.current_fractal_interpolated(elapsed);
+                    view.max_iterations = config.max_iterations;
+                    let kind = if action == Action::ExportField { ExportKind::Field } else { ExportKind::Contours };
+                    render_thread.export(ExportRequest { kind, params: view, settings: config.export.clone() });
+                    app_state.set_status("Exporting...".to_string());
+                }
+                Some(Action::NextColorMode) => {
+                    app_state.next_color_mode();
+                    app_state.set_status(format!("Color mode: {}", app_state.color_mode.name()));
+                    render_thread.invalidate();
+                }
+                Some(Action::NextInteriorMode) => {
+                    app_state.next_interior_mode();
+                    app_state.set_status(format!("Interior: {}", app_state.interior_mode.name()));
+                    render_thread.invalidate();
+                }
+                Some(Action::NextTrapShape) => {
+                    app_state.next_trap_shape();
+                    app_state.color_mode = ColorMode::OrbitTrap;
+                    app_state.set_status(format!("Orbit trap: {}", app_state.trap_shape().name()));
+                    render_thread.invalidate();
+                }
+                Some(Action::MoveTrap) => {
+                    app_state.move_trap();
+                }
+                Some(Action::ZoomIn) => {
+                    app_state.zoom_by(ZOOM_STEP);
+                    render_thread.invalidate();
+                }
+                Some(Action::ZoomOut) => {
+                    app_state.zoom_by(1.0 / ZOOM_STEP);
+                    render_thread.invalidate();
+                }
+                Some(Action::PanLeft) => {
+                    app_state.pan(-PAN_STEP, 0.0);
+                    render_thread.invalidate();
+                }
+                Some(Action::PanRight) => {
+                    app_state.pan(PAN_STEP, 0.0);
+                    render_thread.invalidate();
                 }
+                Some(Action::PanUp) => {
+                    app_state.pan(0.0, -PAN_STEP);
+                    render_thread.invalidate();
+                }
+                Some(Action::PanDown) => {
+                    app_state.pan(0.0, PAN_STEP);
+                    render_thread.invalidate();
+                }
+                Some(Action::ToggleDive) => {
+                    app_state.toggle_dive();
+                    let message = if app_state.dive.enabled { "Dive: on" } else { "Dive: off" };
+                    app_state.set_status(message.to_string());
+                }
+                Some(Action::ToggleHud) => {
+                    app_state.toggle_hud();
+                }
+                Some(Action::TogglePause) => {
+                    app_state.toggle_pause();
+                }
+                Some(Action::ToggleCycling) => {
+                    app_state.color_cycle.toggle();
+                }
+                Some(Action::CycleFaster) => {
+                    app_state.color_cycle.faster();
+                }
+                Some(Action::CycleSlower) => {
+                    app_state.color_cycle.slower();
+                }
+                Some(Action::ReverseCycling) => {
+                    app_state.color_cycle.reverse();
+                }
+                Some(Action::ToggleHelp) => {
+                    app_state.toggle_help();
+                }
+                None => {}
+            }
+        }
+
+        // Collected on every pass, so a finished frame is seen, and the next
+        // one requested, on the first tick after it arrives
+        if let Some(rendered) = render_thread.latest() {
+            if rendered.complete {
+                scheduler.render_finished(rendered.render_time);
+            }
+            if let Some(detail) = rendered.detail {
+                app_state.dive.set_target(detail);
             }
+            frame = Some(rendered.field);
+        }
+
+        while let Some(result) = render_thread.exported() {
+            let message = match result {
+                Ok(path) => format!("Exported {}", path.display()),
+                Err(err) => format!("Export failed: {}", err),
+            };
+            app_state.set_status(message);
+        }
+
+        if !scheduler.next_frame() {
+            continue;
         }
 
         if app_state.show_help {
-            display_help()?;
-            thread::sleep(Duration::from_millis(100));
+            display_help(&keymap, screen_size.width, screen_size.height)?;
             continue;
         }
 
-        let elapsed = app_state.transition_start.elapsed().as_secs_f64();
+        if app_state.advance_dive() {
+            let complexity = measure_complexity(
+                &app_state.current_fractal,
+                screen_size.width,
+                screen_size.height
+            );
+            app_state.update_transition_time(complexity);
+            app_state.set_status("Dive restarted".to_string());
+            render_thread.invalidate();
+        }
+
+        let elapsed = app_state.transition_elapsed();
 
-        if app_state.is_transition_complete(elapsed) {
+        // The transition clock stands still during a dive, and the next
+        // transition waits for the dive to end as well
+        if !app_state.is_paused() && !app_state.dive.enabled && app_state.is_transition_complete(elapsed) {
             let complexity = measure_complexity(
                 &app_state.next_fractal, 
                 screen_size.width, 
@@ -84,18 +281,58 @@ fn main() -> io::Result<()> {
             app_state.start_new_transition(complexity);
         }
 
-        let current_fractal = app_state.current_fractal_interpolated(elapsed);
+        // While paused the geometry is fixed, so only the colors are redrawn
+        if render_thread.needs_request(app_state.is_paused()) {
+            let mut view = app_state.current_fractal_interpolated(elapsed);
+            view.max_iterations = scheduler.max_iterations;
+            render_thread.request(RenderRequest {
+                generation: 0,
+                params: view,
+                width: screen_size.width,
+                height: screen_size.height,
+                value: app_state.color_mode.field_value(),
+                interior_mode: app_state.interior_mode,
+                sample_step: scheduler.sample_step,
+                search_detail: !app_state.is_paused() && app_state.dive.wants_search(),
+            });
+        }
+
+        // A frame computed for another color mode would be drawn wrongly
+        if let Some(field) = &frame
+            && field.value == app_state.color_mode.field_value() {
+            let cycle_offset = app_state.color_cycle.advance();
+            draw_field(field, app_state.color_scheme(), app_state.color_mode, cycle_offset)?;
+        }
 
-        render_fractal(
-            &current_fractal, 
-            app_state.color_scheme,
-            screen_size.width, 
-            screen_size.height
-        )?;
+        if app_state.show_hud {
+            let view = app_state.current_fractal_interpolated(elapsed);
+            let hud = Hud {
+                params: &view,
+                scheme: app_state.color_scheme().name(),
+                max_iterations: frame.as_ref().map_or(scheduler.max_iterations, |field| field.max_iterations),
+                progress: app_state.transition_progress(elapsed),
+                complexity: app_state.complexity,
+                fps: scheduler.fps,
+                palette: match app_state.color_scheme() {
+                    ColorScheme::Cosine(palette) => Some(describe_cosine(palette)),
+                    _ => None,
+                },
+                log: log::latest(),
+            };
+            display_hud(&hud, screen_size.width)?;
+        }
 
-        thread::sleep(Duration::from_millis(50));
+        if let Some(message) = app_state.active_status() {
+            display_status(message, screen_size.width, screen_size.height)?;
+        }
     }
 
     cleanup_terminal()?;
     Ok(())
 }
+
+/// The palette file line for a cosine palette, short enough for the status
+/// line on an 80 column terminal.
+fn describe_cosine(palette: &CosinePalette) -> String {
+    format!("{:.2}", palette)
+}
@@ -1,31 +1,111 @@
+use std::sync::Arc;
+
 use colored::Colorize;
-use crate::fractal::julia::MAX_ITERATIONS;
+use crate::fractal::field::FieldValue;
+use crate::ui::palette::Gradient;
+use crate::ui::palette::cosine::CosinePalette;
+
+/// Number of iterations over which a gradient palette runs once.
+pub const GRADIENT_PERIOD: f64 = 16.0;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorMode {
+    EscapeTime,
+    Histogram,
+    Distance,
+    OrbitTrap,
+    RootBasin,
+}
+
+impl ColorMode {
+    /// The mode after this one, skipping modes that would come out blank:
+    /// root basins for escape-time formulas, and distance estimates for
+    /// root-finding ones.
+    pub fn next(&self, finds_roots: bool) -> Self {
+        match self {
+            ColorMode::EscapeTime => ColorMode::Histogram,
+            ColorMode::Histogram if finds_roots => ColorMode::RootBasin,
+            ColorMode::Histogram => ColorMode::Distance,
+            ColorMode::Distance => ColorMode::OrbitTrap,
+            ColorMode::OrbitTrap if finds_roots => ColorMode::RootBasin,
+            ColorMode::OrbitTrap => ColorMode::EscapeTime,
+            ColorMode::RootBasin => ColorMode::EscapeTime,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorMode::EscapeTime => "Escape time",
+            ColorMode::Histogram => "Histogram equalized",
+            ColorMode::Distance => "Distance estimate",
+            ColorMode::OrbitTrap => "Orbit trap",
+            ColorMode::RootBasin => "Root basin",
+        }
+    }
+
+    pub fn field_value(&self) -> FieldValue {
+        match self {
+            ColorMode::EscapeTime | ColorMode::Histogram => FieldValue::Smooth,
+            ColorMode::Distance => FieldValue::Distance,
+            ColorMode::OrbitTrap => FieldValue::Trap,
+            ColorMode::RootBasin => FieldValue::Root,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ColorScheme {
     Rainbow,
     Blues,
     Greens,
     Purples,
     Grays,
+    Gradient(Arc<Gradient>),
+    Cosine(CosinePalette),
 }
 
 impl ColorScheme {
-    pub fn next(&self) -> Self {
+    pub fn built_in() -> Vec<Self> {
+        vec![
+            ColorScheme::Rainbow,
+            ColorScheme::Blues,
+            ColorScheme::Greens,
+            ColorScheme::Purples,
+            ColorScheme::Grays,
+        ]
+    }
+
+    pub fn name(&self) -> &str {
         match self {
-            ColorScheme::Rainbow => ColorScheme::Blues,
-            ColorScheme::Blues => ColorScheme::Greens,
-            ColorScheme::Greens => ColorScheme::Purples,
-            ColorScheme::Purples => ColorScheme::Grays,
-            ColorScheme::Grays => ColorScheme::Rainbow,
+            ColorScheme::Rainbow => "Rainbow",
+            ColorScheme::Blues => "Blues",
+            ColorScheme::Greens => "Greens",
+            ColorScheme::Purples => "Purples",
+            ColorScheme::Grays => "Grays",
+            ColorScheme::Gradient(gradient) => &gradient.name,
+            ColorScheme::Cosine(_) => "Cosine",
+        }
+    }
+
+    /// Range of color indices the scheme distinguishes before repeating
+    /// or saturating.
+    pub fn span(&self) -> f64 {
+        match self {
+            ColorScheme::Rainbow => 16.0,
+            ColorScheme::Blues | ColorScheme::Greens | ColorScheme::Purples | ColorScheme::Grays => 10.0,
+            ColorScheme::Gradient(_) | ColorScheme::Cosine(_) => GRADIENT_PERIOD,
         }
     }
 }
 
-pub fn get_color(iterations: u32, scheme: ColorScheme) -> String {
-    if iterations == MAX_ITERATIONS {
+/// Colors a smooth escape value, or `None` for points on the set. Built-in
+/// schemes use its integer part; gradients interpolate across the fraction
+/// as well.
+pub fn get_color(escape: Option<f64>, scheme: &ColorScheme) -> String {
+    let Some(escape) = escape else {
         return " ".on_black().to_string();
-    }
+    };
+    let iterations = escape as u32;
 
     let char_selector = iterations % 4;
 
@@ -59,6 +139,14 @@ pub fn get_color(iterations: u32, scheme: ColorScheme) -> String {
         ColorScheme::Greens => color_gradient(iterations, base_char, "green"),
         ColorScheme::Purples => color_gradient(iterations, base_char, "magenta"),
         ColorScheme::Grays => color_gradient(iterations, base_char, "gray"),
+        ColorScheme::Gradient(gradient) => {
+            let (r, g, b) = gradient.sample((escape / GRADIENT_PERIOD).fract());
+            base_char.truecolor(r, g, b).to_string()
+        }
+        ColorScheme::Cosine(palette) => {
+            let (r, g, b) = palette.sample((escape / GRADIENT_PERIOD).fract());
+            base_char.truecolor(r, g, b).to_string()
+        }
     }
 }
 
@@ -109,7 +197,7 @@ fn color_gradient(iterations: u32, base_char: &str, color_type: &str) -> String
                 base_char.black().on_black().to_string()
             }
         },
-        "gray" | _ => {
+        _ => {
             if iterations > 8 {
                 base_char.bright_white().on_white().to_string()
             } else if iterations > 6 {
@@ -0,0 +1,41 @@
+use crate::fractal::field::IterationField;
+
+/// Maps escape values to their rank among the escaped points of a frame,
+/// so that every palette color covers roughly the same screen area.
+pub struct Equalizer {
+    cdf: Vec<f64>,
+}
+
+impl Equalizer {
+    pub fn new(field: &IterationField) -> Self {
+        let interior = field.max_iterations;
+        let mut counts = vec![0u32; interior as usize];
+        let mut total = 0u32;
+
+        for &escape in &field.values {
+            if escape < interior as f64 {
+                counts[escape as usize] += 1;
+                total += 1;
+            }
+        }
+
+        // cdf[i] is the fraction of escaped points below i iterations
+        let mut cdf = Vec::with_capacity(counts.len() + 1);
+        let mut running = 0u32;
+        cdf.push(0.0);
+        for count in counts {
+            running += count;
+            cdf.push(if total > 0 { running as f64 / total as f64 } else { 0.0 });
+        }
+
+        Self { cdf }
+    }
+
+    /// Returns the equalized position in `[0, 1]`, interpolating across the
+    /// fractional part of smooth escape values.
+    pub fn rank(&self, escape: f64) -> f64 {
+        let bucket = escape as usize;
+        let fraction = escape.fract();
+        self.cdf[bucket] + (self.cdf[bucket + 1] - self.cdf[bucket]) * fraction
+    }
+}
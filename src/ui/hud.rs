@@ -0,0 +1,73 @@
+use crossterm::{cursor, execute, style};
+use std::io::{self, Write, stdout};
+
+use crate::fractal::parameters::FractalParameters;
+
+/// Everything the heads-up display reports about the current frame.
+pub struct Hud<'a> {
+    pub params: &'a FractalParameters,
+    pub scheme: &'a str,
+    /// Iteration cap of the frame on screen
+    pub max_iterations: u32,
+    pub progress: f64,
+    pub complexity: f64,
+    pub fps: f64,
+    /// Coefficients of the active cosine palette, as a palette file line
+    pub palette: Option<String>,
+    /// Latest diagnostic message, when logging is on
+    pub log: Option<String>,
+}
+
+impl Hud<'_> {
+    pub fn lines(&self) -> Vec<String> {
+        let params = self.params;
+        // Enough digits of the center to tell neighboring pixels apart
+        let digits = params.zoom.log10().ceil().max(0.0) as usize + 6;
+
+        let mut lines = vec![
+            format!(
+                " c = {:.6}{:+.6}i  zoom {:.3e}  center {:.digits$}, {:.digits$} ",
+                params.c.re,
+                params.c.im,
+                params.zoom,
+                params.x_offset,
+                params.y_offset,
+            ),
+            format!(
+                " {}  scheme {}  iterations {}  transition {:.0}%  complexity {:.2}  {:.1} fps ",
+                params.formula,
+                self.scheme,
+                self.max_iterations,
+                self.progress * 100.0,
+                self.complexity,
+                self.fps,
+            ),
+        ];
+        if let Some(palette) = &self.palette {
+            lines.push(format!(" {} ", palette));
+        }
+        if let Some(message) = &self.log {
+            lines.push(format!(" {} ", message));
+        }
+        lines
+    }
+}
+
+/// Draws the HUD over the top rows of the frame.
+pub fn display_hud(hud: &Hud, width: u16) -> io::Result<()> {
+    let mut stdout = stdout();
+
+    for (row, line) in hud.lines().iter().enumerate() {
+        let line: String = line.chars().take(width as usize).collect();
+        execute!(
+            stdout,
+            cursor::MoveTo(0, row as u16),
+            style::SetAttribute(style::Attribute::Reverse),
+            style::Print(line),
+            style::SetAttribute(style::Attribute::Reset),
+        )?;
+    }
+
+    stdout.flush()?;
+    Ok(())
+}
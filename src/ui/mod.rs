@@ -1,3 +1,7 @@
 pub mod colors;
+pub mod histogram;
+pub mod hud;
+pub mod palette;
+pub mod render_thread;
 pub mod renderer;
 pub mod terminal;
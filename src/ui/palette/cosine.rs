@@ -0,0 +1,101 @@
+use std::f64::consts::TAU;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use super::{ColorStop, Gradient, Rgb};
+
+/// Stops used when a cosine palette is baked into a gradient.
+const BAKED_STOPS: usize = 64;
+
+/// A procedural palette `a + b * cos(2π(c * t + d))`, evaluated per RGB
+/// channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CosinePalette {
+    pub a: [f64; 3],
+    pub b: [f64; 3],
+    pub c: [f64; 3],
+    pub d: [f64; 3],
+    pub seed: Option<u64>,
+}
+
+impl CosinePalette {
+    /// Generates coefficients deterministically from `seed`, biased towards
+    /// bright palettes that stay inside the displayable range.
+    pub fn from_seed(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut channel = |range: std::ops::Range<f64>| [0; 3].map(|_| rng.gen_range(range.clone()));
+
+        Self {
+            a: channel(0.35..0.65),
+            b: channel(0.25..0.5),
+            c: channel(0.5..1.5),
+            d: channel(0.0..1.0),
+            seed: Some(seed),
+        }
+    }
+
+    /// Parses the `a b c d` coefficient triples written by `Display`, each
+    /// triple comma separated.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut triples = text.split_whitespace().map(|triple| {
+            let mut values = triple.split(',').map(|v| v.parse::<f64>().ok());
+            let result = [values.next()??, values.next()??, values.next()??];
+            values.next().is_none().then_some(result)
+        });
+
+        let palette = Self {
+            a: triples.next()??,
+            b: triples.next()??,
+            c: triples.next()??,
+            d: triples.next()??,
+            seed: None,
+        };
+        triples.next().is_none().then_some(palette)
+    }
+
+    pub fn sample(&self, t: f64) -> Rgb {
+        let channel = |i: usize| {
+            let value = self.a[i] + self.b[i] * (TAU * (self.c[i] * t + self.d[i])).cos();
+            (value.clamp(0.0, 1.0) * 255.0).round() as u8
+        };
+        (channel(0), channel(1), channel(2))
+    }
+
+    /// Writes the coefficients as a native palette file in `dir`, so the
+    /// palette is loaded with the others on the next start.
+    pub fn save(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let name = match self.seed {
+            Some(seed) => format!("cosine-{}", seed),
+            None => "cosine".to_string(),
+        };
+        let path = dir.join(format!("{}.palette", name));
+        fs::write(&path, format!("name {}\n{}\n", name, self))?;
+        Ok(path)
+    }
+
+    pub fn to_gradient(self, name: String) -> Gradient {
+        let stops = (0..BAKED_STOPS)
+            .map(|i| {
+                let position = i as f64 / (BAKED_STOPS - 1) as f64;
+                ColorStop { position, color: self.sample(position) }
+            })
+            .collect();
+        Gradient::new(name, stops)
+    }
+}
+
+/// Writes the palette file line, with 3 decimals unless a format
+/// precision asks for another number.
+impl fmt::Display for CosinePalette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = f.precision().unwrap_or(3);
+        let triple = |v: [f64; 3]| format!("{:.digits$},{:.digits$},{:.digits$}", v[0], v[1], v[2]);
+        write!(f, "cosine {} {} {} {}", triple(self.a), triple(self.b), triple(self.c), triple(self.d))
+    }
+}
@@ -0,0 +1,62 @@
+//! GIMP palettes: a `GIMP Palette` header, optional `Name:` and `Columns:`
+//! lines, then one `R G B [name]` entry per line with decimal channels.
+
+use std::path::Path;
+
+use super::{Gradient, PaletteError, parse_channels};
+
+pub fn parse(path: &Path, contents: &str, default_name: String) -> Result<Gradient, PaletteError> {
+    let mut lines = contents.lines().enumerate();
+
+    match lines.next() {
+        Some((_, header)) if header.trim() == "GIMP Palette" => {}
+        _ => return Err(PaletteError::at_line(path, 1, "expected 'GIMP Palette' header")),
+    }
+
+    let mut name = default_name;
+    let mut colors = Vec::new();
+
+    for (index, line) in lines {
+        let line_number = index + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') || line.starts_with("Columns:") {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("Name:") {
+            name = rest.trim().to_string();
+            continue;
+        }
+
+        let color = parse_channels(line)
+            .ok_or_else(|| PaletteError::at_line(path, line_number, "expected 'R G B' with values 0 to 255"))?;
+        colors.push(color);
+    }
+
+    Gradient::from_colors(name, colors).ok_or_else(|| PaletteError::in_file(path, "palette has no colors"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(contents: &str) -> Result<Gradient, PaletteError> {
+        parse(Path::new("test.gpl"), contents, "test".to_string())
+    }
+
+    #[test]
+    fn parses_colors() {
+        let gradient = load("GIMP Palette\nName: Sunset\nColumns: 4\n# comment\n255 0 0 Red\n  0 0 255\tBlue\n").unwrap();
+        assert_eq!(gradient.name, "Sunset");
+        assert_eq!(gradient.sample(0.0), (255, 0, 0));
+        assert_eq!(gradient.sample(1.0), (0, 0, 255));
+    }
+
+    #[test]
+    fn reports_errors_by_line() {
+        assert_eq!(load("Palette\n0 0 0").unwrap_err().line, Some(1));
+        assert_eq!(load("GIMP Palette\n0 0 0\n256 0 0").unwrap_err().line, Some(3));
+        assert_eq!(load("GIMP Palette\nName: Empty").unwrap_err().line, None);
+    }
+}
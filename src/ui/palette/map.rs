@@ -0,0 +1,47 @@
+//! Fractint color maps: one `R G B` entry per line with decimal channels,
+//! optionally followed by a comment. Usually 256 entries.
+
+use std::path::Path;
+
+use super::{Gradient, PaletteError, parse_channels};
+
+pub fn parse(path: &Path, contents: &str, default_name: String) -> Result<Gradient, PaletteError> {
+    let mut colors = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+
+        let color = parse_channels(line)
+            .ok_or_else(|| PaletteError::at_line(path, index + 1, "expected 'R G B' with values 0 to 255"))?;
+        colors.push(color);
+    }
+
+    Gradient::from_colors(default_name, colors).ok_or_else(|| PaletteError::in_file(path, "map has no colors"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(contents: &str) -> Result<Gradient, PaletteError> {
+        parse(Path::new("test.map"), contents, "test".to_string())
+    }
+
+    #[test]
+    fn parses_colors() {
+        let gradient = load("; comment\n0 0 0 black\n128 64 32\n255 255 255 white\n").unwrap();
+        assert_eq!(gradient.name, "test");
+        assert_eq!(gradient.stops.len(), 3);
+        assert_eq!(gradient.sample(0.5), (128, 64, 32));
+    }
+
+    #[test]
+    fn reports_errors_by_line() {
+        assert_eq!(load("0 0 0\n\n1 2").unwrap_err().line, Some(3));
+        assert_eq!(load("; only comments").unwrap_err().line, None);
+    }
+}
@@ -0,0 +1,161 @@
+pub mod cosine;
+pub mod gpl;
+pub mod map;
+pub mod stops;
+pub mod ugr;
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub type Rgb = (u8, u8, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorStop {
+    pub position: f64,
+    pub color: Rgb,
+}
+
+/// A palette defined by color stops on `[0, 1]`, sampled with linear
+/// interpolation between neighboring stops.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub name: String,
+    pub stops: Vec<ColorStop>,
+}
+
+impl Gradient {
+    pub fn new(name: String, mut stops: Vec<ColorStop>) -> Self {
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        Self { name, stops }
+    }
+
+    /// Spreads `colors` evenly across the gradient, first to last.
+    pub fn from_colors(name: String, colors: Vec<Rgb>) -> Option<Self> {
+        let last = colors.len().checked_sub(1)?.max(1) as f64;
+        let stops = colors
+            .into_iter()
+            .enumerate()
+            .map(|(i, color)| ColorStop { position: i as f64 / last, color })
+            .collect();
+        Some(Self::new(name, stops))
+    }
+
+    pub fn sample(&self, t: f64) -> Rgb {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return (0, 0, 0),
+        };
+
+        if t <= first.position {
+            return first.color;
+        }
+        if t >= last.position {
+            return last.color;
+        }
+
+        let upper = self.stops.iter().position(|stop| stop.position >= t).unwrap_or(self.stops.len() - 1);
+        let (a, b) = (&self.stops[upper - 1], &self.stops[upper]);
+        let span = b.position - a.position;
+        let f = if span > 0.0 { (t - a.position) / span } else { 0.0 };
+
+        (
+            lerp_channel(a.color.0, b.color.0, f),
+            lerp_channel(a.color.1, b.color.1, f),
+            lerp_channel(a.color.2, b.color.2, f),
+        )
+    }
+}
+
+fn lerp_channel(a: u8, b: u8, t: f64) -> u8 {
+    (a as f64 + (b as f64 - a as f64) * t).round() as u8
+}
+
+#[derive(Debug)]
+pub struct PaletteError {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl PaletteError {
+    pub fn at_line(path: &Path, line: usize, message: impl Into<String>) -> Self {
+        Self { path: path.to_path_buf(), line: Some(line), message: message.into() }
+    }
+
+    pub fn in_file(path: &Path, message: impl Into<String>) -> Self {
+        Self { path: path.to_path_buf(), line: None, message: message.into() }
+    }
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+impl std::error::Error for PaletteError {}
+
+/// Parses the leading `R G B` decimal triple of a line, ignoring anything
+/// after it.
+pub(crate) fn parse_channels(line: &str) -> Option<Rgb> {
+    let mut fields = line.split_whitespace().map(|field| field.parse::<u8>().ok());
+    Some((fields.next()??, fields.next()??, fields.next()??))
+}
+
+const EXTENSIONS: [&str; 4] = ["palette", "gpl", "map", "ugr"];
+
+/// Loads a palette file, picking the parser from its extension. Ultra
+/// Fractal collections may hold several gradients.
+pub fn load_palette(path: &Path) -> Result<Vec<Gradient>, PaletteError> {
+    let contents = fs::read(path).map_err(|err| PaletteError::in_file(path, err.to_string()))?;
+    // Palette files in the wild are often Latin-1 rather than UTF-8
+    let contents = String::from_utf8_lossy(&contents);
+    let default_name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("palette") => stops::parse(path, &contents, default_name).map(|g| vec![g]),
+        Some("gpl") => gpl::parse(path, &contents, default_name).map(|g| vec![g]),
+        Some("map") => map::parse(path, &contents, default_name).map(|g| vec![g]),
+        Some("ugr") => ugr::parse(path, &contents),
+        _ => Err(PaletteError::in_file(path, "unrecognized palette format")),
+    }
+}
+
+/// Loads every palette file in `dir`, sorted by file name. A missing
+/// directory is not an error; unreadable or malformed files are reported
+/// and skipped.
+pub fn load_palettes(dir: &Path) -> (Vec<Gradient>, Vec<PaletteError>) {
+    let mut gradients = Vec::new();
+    let mut errors = Vec::new();
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return (gradients, errors),
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| EXTENSIONS.contains(&ext))
+        })
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        match load_palette(&path) {
+            Ok(loaded) => gradients.extend(loaded),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    (gradients, errors)
+}
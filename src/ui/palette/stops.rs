@@ -0,0 +1,121 @@
+//! The native palette format: one `position color` stop per line, where
+//! position is in `[0, 1]` and color is six hex digits with an optional
+//! leading `#`. Lines starting with `#` are comments and an optional
+//! `name <text>` line overrides the file name. Instead of stops, a file may
+//! hold a single `cosine a b c d` line of procedural coefficients.
+//!
+//! ```text
+//! # Ultra Fractal default
+//! name Ultra
+//! 0.0    #000764
+//! 0.16   #206bcb
+//! 0.42   #edffff
+//! 0.6425 #ffaa00
+//! 0.8575 #000200
+//! ```
+
+use std::path::Path;
+
+use super::cosine::CosinePalette;
+use super::{ColorStop, Gradient, PaletteError, Rgb};
+
+pub fn parse(path: &Path, contents: &str, default_name: String) -> Result<Gradient, PaletteError> {
+    let mut name = default_name;
+    let mut stops = Vec::new();
+    let mut cosine = None;
+
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("name ") {
+            name = rest.trim().to_string();
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("cosine ") {
+            let palette = CosinePalette::parse(rest)
+                .ok_or_else(|| PaletteError::at_line(path, line_number, "expected 'cosine r,g,b r,g,b r,g,b r,g,b'"))?;
+            cosine = Some(palette);
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let (position, color) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(position), Some(color), None) => (position, color),
+            _ => return Err(PaletteError::at_line(path, line_number, "expected '<position> <#rrggbb>'")),
+        };
+
+        let position: f64 = position
+            .parse()
+            .ok()
+            .filter(|p: &f64| (0.0..=1.0).contains(p))
+            .ok_or_else(|| PaletteError::at_line(path, line_number, format!("invalid position '{}', expected 0.0 to 1.0", position)))?;
+        let color = parse_hex(color)
+            .ok_or_else(|| PaletteError::at_line(path, line_number, format!("invalid color '{}', expected #rrggbb", color)))?;
+
+        stops.push(ColorStop { position, color });
+    }
+
+    match (cosine, stops.is_empty()) {
+        (Some(palette), true) => return Ok(palette.to_gradient(name)),
+        (Some(_), false) => return Err(PaletteError::in_file(path, "palette mixes cosine coefficients and color stops")),
+        (None, _) => {}
+    }
+
+    if stops.is_empty() {
+        return Err(PaletteError::in_file(path, "palette has no color stops"));
+    }
+
+    Ok(Gradient::new(name, stops))
+}
+
+pub fn parse_hex(text: &str) -> Option<Rgb> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+
+    let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).ok();
+    Some((channel(0..2)?, channel(2..4)?, channel(4..6)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(contents: &str) -> Result<Gradient, PaletteError> {
+        parse(Path::new("test.palette"), contents, "test".to_string())
+    }
+
+    #[test]
+    fn parses_stops_and_cosine() {
+        let gradient = load("# comment\nname Fire\n1.0 ffffff\n0.0 #000000\n").unwrap();
+        assert_eq!(gradient.name, "Fire");
+        assert_eq!(gradient.stops, [
+            ColorStop { position: 0.0, color: (0, 0, 0) },
+            ColorStop { position: 1.0, color: (255, 255, 255) },
+        ]);
+        assert_eq!(gradient.sample(0.5), (128, 128, 128));
+
+        let gradient = load("cosine 0.5,0.5,0.5 0.5,0.5,0.5 1,1,1 0,0.5,0.5").unwrap();
+        assert_eq!(gradient.name, "test");
+        assert_eq!(gradient.stops.first().map(|stop| stop.color), Some((255, 0, 0)));
+    }
+
+    #[test]
+    fn reports_errors_by_line() {
+        let line = |contents: &str| load(contents).unwrap_err().line;
+        assert_eq!(line("0.0 #000000\n1.5 #ffffff"), Some(2));
+        assert_eq!(line("\n\n0.0 #00000g"), Some(3));
+        assert_eq!(line("0.0"), Some(1));
+        assert_eq!(line("cosine 1,2,3"), Some(1));
+        assert_eq!(line("# only a comment"), None);
+        assert_eq!(line("cosine 0,0,0 0,0,0 0,0,0 0,0,0\n0.0 #000000"), None);
+        assert_eq!(parse_hex("#ÿÿÿ"), None);
+    }
+}
@@ -0,0 +1,185 @@
+//! Ultra Fractal gradient collections. Each `Name { ... }` entry holds a
+//! `gradient:` section of `index=N color=C` points, where the index runs
+//! from 0 to 399 and the color is a decimal `0xBBGGRR` integer. The
+//! gradient wraps from its last point back to its first. Opacity sections
+//! are ignored.
+
+use std::path::Path;
+
+use super::{ColorStop, Gradient, PaletteError, lerp_channel};
+
+const INDEX_RANGE: f64 = 400.0;
+
+pub fn parse(path: &Path, contents: &str) -> Result<Vec<Gradient>, PaletteError> {
+    let mut gradients = Vec::new();
+    let mut entry: Option<(String, Vec<ColorStop>)> = None;
+    let mut in_gradient = false;
+
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_suffix('{') {
+            if entry.is_some() {
+                return Err(PaletteError::at_line(path, line_number, "gradient entry opened inside another"));
+            }
+            entry = Some((name.trim().to_string(), Vec::new()));
+            in_gradient = false;
+            continue;
+        }
+
+        let Some((name, stops)) = entry.as_mut() else {
+            return Err(PaletteError::at_line(path, line_number, "expected 'Name {' to open a gradient entry"));
+        };
+
+        if line == "}" {
+            let (name, stops) = entry.take().unwrap_or_default();
+            if stops.is_empty() {
+                return Err(PaletteError::at_line(path, line_number, format!("gradient '{}' has no color points", name)));
+            }
+            gradients.push(Gradient::new(name, close_cycle(stops)));
+            continue;
+        }
+
+        if let Some(section) = line.strip_suffix(':') {
+            in_gradient = section == "gradient";
+            continue;
+        }
+
+        if !in_gradient {
+            continue;
+        }
+
+        if let Some(title) = quoted_value(line, "title") {
+            *name = title.to_string();
+            continue;
+        }
+
+        let mut position = None;
+        let mut color = None;
+
+        for field in line.split_whitespace() {
+            let Some((key, value)) = field.split_once('=') else {
+                continue;
+            };
+            match key {
+                "index" => {
+                    let index: f64 = value
+                        .parse()
+                        .map_err(|_| PaletteError::at_line(path, line_number, format!("invalid index '{}'", value)))?;
+                    position = Some((index / INDEX_RANGE).rem_euclid(1.0));
+                }
+                "color" => {
+                    let bgr: u32 = value
+                        .parse()
+                        .map_err(|_| PaletteError::at_line(path, line_number, format!("invalid color '{}'", value)))?;
+                    color = Some(((bgr & 0xff) as u8, (bgr >> 8 & 0xff) as u8, (bgr >> 16 & 0xff) as u8));
+                }
+                _ => {}
+            }
+        }
+
+        match (position, color) {
+            (Some(position), Some(color)) => stops.push(ColorStop { position, color }),
+            (None, None) => {}
+            _ => return Err(PaletteError::at_line(path, line_number, "expected both 'index=' and 'color='")),
+        }
+    }
+
+    if let Some((name, _)) = entry {
+        return Err(PaletteError::in_file(path, format!("gradient '{}' is missing its closing '}}'", name)));
+    }
+    if gradients.is_empty() {
+        return Err(PaletteError::in_file(path, "file contains no gradients"));
+    }
+
+    Ok(gradients)
+}
+
+/// Ultra Fractal gradients are cyclic: past the last point they blend back
+/// into the first. Adds stops at both ends with the color the wrap passes
+/// through, so linear sampling on `[0, 1]` covers the gap.
+fn close_cycle(mut stops: Vec<ColorStop>) -> Vec<ColorStop> {
+    stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+    let (Some(first), Some(last)) = (stops.first().copied(), stops.last().copied()) else {
+        return stops;
+    };
+
+    let gap = 1.0 - last.position + first.position;
+    let t = if gap > 0.0 { (1.0 - last.position) / gap } else { 0.0 };
+    let color = (
+        lerp_channel(last.color.0, first.color.0, t),
+        lerp_channel(last.color.1, first.color.1, t),
+        lerp_channel(last.color.2, first.color.2, t),
+    );
+
+    if first.position > 0.0 {
+        stops.insert(0, ColorStop { position: 0.0, color });
+    }
+    stops.push(ColorStop { position: 1.0, color });
+    stops
+}
+
+fn quoted_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let start = line.find(&format!("{}=\"", key))? + key.len() + 2;
+    let length = line[start..].find('"')?;
+    Some(&line[start..start + length])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(contents: &str) -> Result<Vec<Gradient>, PaletteError> {
+        parse(Path::new("test.ugr"), contents)
+    }
+
+    #[test]
+    fn parses_collections() {
+        let contents = "\
+first {
+gradient:
+  title=\"Red Blue\" smooth=no
+  index=100 color=255
+  index=300 color=16711680
+opacity:
+  index=0 opacity=255
+}
+; between entries
+second {
+gradient:
+  index=0 color=65280
+}
+";
+        let gradients = load(contents).unwrap();
+        assert_eq!(gradients.len(), 2);
+        assert_eq!(gradients[0].name, "Red Blue");
+        assert_eq!(gradients[1].name, "second");
+
+        // Colors are 0xBBGGRR, and the wrap from blue at 0.75 back to red
+        // at 0.25 passes through the midpoint at 0 and 1
+        let red_blue = &gradients[0];
+        assert_eq!(red_blue.sample(0.25), (255, 0, 0));
+        assert_eq!(red_blue.sample(0.75), (0, 0, 255));
+        assert_eq!(red_blue.sample(0.0), (128, 0, 128));
+        assert_eq!(red_blue.sample(1.0), red_blue.sample(0.0));
+        assert_eq!(gradients[1].sample(0.5), (0, 255, 0));
+    }
+
+    #[test]
+    fn reports_errors_by_line() {
+        let line = |contents: &str| load(contents).unwrap_err().line;
+        assert_eq!(line("index=0 color=0"), Some(1));
+        assert_eq!(line("a {\ngradient:\nindex=x color=0\n}"), Some(3));
+        assert_eq!(line("a {\ngradient:\nindex=0 color=-1\n}"), Some(3));
+        assert_eq!(line("a {\ngradient:\nindex=0\n}"), Some(3));
+        assert_eq!(line("a {\nb {"), Some(2));
+        assert_eq!(line("a {\ngradient:\n}"), Some(3));
+        assert_eq!(line("a {\ngradient:\nindex=0 color=0"), None);
+        assert_eq!(line("; empty"), None);
+    }
+}
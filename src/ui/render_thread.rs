@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use num_complex::Complex;
+
+use crate::export::{ExportSettings, export_contours, export_field};
+use crate::fractal::detail::find_detail;
+use crate::fractal::field::{FieldValue, IterationField};
+use crate::fractal::interior::InteriorMode;
+use crate::fractal::parameters::FractalParameters;
+use crate::fractal::progressive::ProgressiveField;
+use crate::utils::PREVIEW_DELAY;
+
+/// A snapshot of everything needed to compute one frame.
+pub struct RenderRequest {
+    /// Assigned by `RenderThread::request`
+    pub generation: u64,
+    pub params: FractalParameters,
+    pub width: u16,
+    pub height: u16,
+    pub value: FieldValue,
+    pub interior_mode: InteriorMode,
+    /// Block size the frame is refined down to, 1 for every pixel
+    pub sample_step: u16,
+    /// Also look for the dive's next target once the frame is done
+    pub search_detail: bool,
+}
+
+pub struct RenderedFrame {
+    pub generation: u64,
+    pub field: IterationField,
+    /// False for coarse previews of a frame still being refined
+    pub complete: bool,
+    /// Time spent computing the field, measured on the worker
+    pub render_time: Duration,
+    /// The result of `find_detail`, on the complete frame of a request that
+    /// asked for it
+    pub detail: Option<Option<Complex<f64>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportKind {
+    Field,
+    Contours,
+}
+
+/// A full-resolution export of one view.
+pub struct ExportRequest {
+    pub kind: ExportKind,
+    pub params: FractalParameters,
+    pub settings: ExportSettings,
+}
+
+impl ExportRequest {
+    fn run(&self) -> io::Result<PathBuf> {
+        match self.kind {
+            ExportKind::Field => export_field(&self.params, &self.settings),
+            ExportKind::Contours => export_contours(&self.params, &self.settings),
+        }
+    }
+}
+
+enum Job {
+    Render(RenderRequest),
+    Export(ExportRequest),
+}
+
+/// Computes frames and exports on a worker thread so the input loop never
+/// waits on one. Requests are numbered; the worker abandons a frame as
+/// soon as a newer request arrives, and only the newest frame is handed
+/// back. Exports are never dropped; they run in order once the frame in
+/// progress is done.
+pub struct RenderThread {
+    jobs: Sender<Job>,
+    frames: Receiver<RenderedFrame>,
+    exports: Receiver<io::Result<PathBuf>>,
+    generation: u64,
+    completed: u64,
+    stale: bool,
+}
+
+impl RenderThread {
+    pub fn spawn() -> Self {
+        let (jobs, job_rx) = mpsc::channel();
+        let (frame_tx, frames) = mpsc::channel();
+        let (export_tx, exports) = mpsc::channel();
+        thread::spawn(move || run(job_rx, frame_tx, export_tx));
+
+        Self { jobs, frames, exports, generation: 0, completed: 0, stale: true }
+    }
+
+    /// Forces a new frame even while paused, for changes to the view or to
+    /// what is computed.
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// Whether a new frame should be requested. While animating, the next
+    /// one is requested once the last has finished.
+    pub fn needs_request(&self, paused: bool) -> bool {
+        self.stale || (!paused && self.completed == self.generation)
+    }
+
+    pub fn request(&mut self, mut request: RenderRequest) {
+        self.generation += 1;
+        self.stale = false;
+        request.generation = self.generation;
+        // A closed channel means the worker has gone; there is nothing
+        // useful to do about it here, and no frames will arrive
+        let _ = self.jobs.send(Job::Render(request));
+    }
+
+    pub fn export(&mut self, request: ExportRequest) {
+        let _ = self.jobs.send(Job::Export(request));
+    }
+
+    /// The result of an export finished since the last call, if any.
+    pub fn exported(&self) -> Option<io::Result<PathBuf>> {
+        self.exports.try_recv().ok()
+    }
+
+    /// The newest frame received since the last call, if any. Frames for
+    /// superseded requests are dropped.
+    pub fn latest(&mut self) -> Option<RenderedFrame> {
+        let mut latest: Option<RenderedFrame> = None;
+        for frame in self.frames.try_iter() {
+            if frame.generation != self.generation {
+                continue;
+            }
+            if frame.complete {
+                self.completed = frame.generation;
+            }
+            latest = Some(frame);
+        }
+        latest
+    }
+}
+
+fn run(jobs: Receiver<Job>, frames: Sender<RenderedFrame>, exported: Sender<io::Result<PathBuf>>) {
+    let mut pending = None;
+    let mut exports: VecDeque<ExportRequest> = VecDeque::new();
+
+    loop {
+        while let Some(export) = exports.pop_front() {
+            if exported.send(export.run()).is_err() {
+                return;
+            }
+        }
+
+        let mut request = match pending.take() {
+            Some(request) => request,
+            None => match jobs.recv() {
+                Ok(Job::Render(request)) => request,
+                Ok(Job::Export(export)) => {
+                    exports.push_back(export);
+                    continue;
+                }
+                Err(_) => return,
+            },
+        };
+        for job in jobs.try_iter() {
+            match job {
+                Job::Render(newer) => request = newer,
+                Job::Export(export) => exports.push_back(export),
+            }
+        }
+
+        let generation = request.generation;
+        let search = request.search_detail.then(|| (request.params.clone(), request.width, request.height));
+        let mut render = ProgressiveField::new(
+            request.params,
+            request.width,
+            request.height,
+            request.value,
+            request.interior_mode,
+            request.sample_step,
+        );
+
+        // Coarse passes are only shown when the full frame is slow
+        let started = Instant::now();
+        while !render.is_complete() {
+            let finished_pass = render.refine(|| {
+                for job in jobs.try_iter() {
+                    match job {
+                        Job::Render(newer) => pending = Some(newer),
+                        Job::Export(export) => exports.push_back(export),
+                    }
+                }
+                pending.is_some()
+            });
+            if !finished_pass {
+                break;
+            }
+
+            if !render.is_complete() && started.elapsed().as_secs_f64() > PREVIEW_DELAY {
+                let preview = RenderedFrame {
+                    generation,
+                    field: render.field().clone(),
+                    complete: false,
+                    render_time: started.elapsed(),
+                    detail: None,
+                };
+                if frames.send(preview).is_err() {
+                    return;
+                }
+            }
+        }
+
+        if pending.is_none() {
+            let render_time = started.elapsed();
+            let detail = search.map(|(params, width, height)| find_detail(&params, width, height));
+            let frame = RenderedFrame { generation, field: render.into_field(), complete: true, render_time, detail };
+            if frames.send(frame).is_err() {
+                return;
+            }
+        }
+    }
+}
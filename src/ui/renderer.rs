@@ -1,23 +1,64 @@
 use std::io::{self, Write, stdout};
 use crossterm::{cursor, execute};
 
-use crate::fractal::parameters::FractalParameters;
-use crate::fractal::julia::{calculate_iterations, map_point_to_complex};
-use crate::ui::colors::{ColorScheme, get_color};
+use crate::fractal::field::IterationField;
+use crate::ui::colors::{ColorMode, ColorScheme, get_color};
+use crate::ui::histogram::Equalizer;
 
-pub fn render_fractal(params: &FractalParameters, color_scheme: ColorScheme, width: u16, height: u16) -> io::Result<()> {
+/// Distance, in pixels, over which distance shading fades to the darkest
+/// color.
+const DISTANCE_FALLOFF: f64 = 8.0;
+
+/// How quickly orbit trap shading fades with distance from the trap.
+const TRAP_FALLOFF: f64 = 4.0;
+
+/// Share of the palette, next to each root's own color, used to shade its
+/// basin by convergence speed.
+const BASIN_SHADING: f64 = 0.15;
+
+/// Colors an already computed field. With a cycle offset, color indices
+/// are shifted and wrapped to the scheme's span. Points on the set use the
+/// field's interior shades when it has them.
+pub fn draw_field(field: &IterationField, color_scheme: &ColorScheme, color_mode: ColorMode, cycle_offset: Option<f64>) -> io::Result<()> {
     let mut stdout = stdout();
 
     execute!(stdout, cursor::MoveTo(0, 0))?;
 
-    for y in 0..height {
-        for x in 0..width {
-            let z = map_point_to_complex(x, y, width, height, params);
-            let iterations = calculate_iterations(z, params.c);
-            let color_char = get_color(iterations, color_scheme);
+    let span = color_scheme.span();
+    let interior = field.max_iterations as f64;
+    let equalizer = match color_mode {
+        ColorMode::Histogram => Some(Equalizer::new(field)),
+        _ => None,
+    };
+
+    for (i, &value) in field.values.iter().enumerate() {
+        // `None` marks points drawn as part of the set. In distance mode
+        // that includes anything within a pixel of it, which keeps thin
+        // filaments visible at low resolution.
+        let index = match (color_mode, &equalizer) {
+            (ColorMode::Histogram, Some(equalizer)) if value < interior => Some(equalizer.rank(value) * span * 0.999),
+            (ColorMode::Distance, _) if value >= 1.0 => {
+                let shade = 1.0 - (value / DISTANCE_FALLOFF).sqrt().min(1.0);
+                Some(shade * span * 0.999)
+            }
+            (ColorMode::OrbitTrap, _) if value.is_finite() => Some((-value * TRAP_FALLOFF).exp() * span * 0.999),
+            (ColorMode::RootBasin, _) if value.is_finite() => {
+                // Golden ratio steps give each root a well separated color
+                let hue = (value.floor() * 0.618_033_988_75).fract();
+                let position = (hue + BASIN_SHADING * value.fract().sqrt()).fract();
+                Some(position * span * 0.999)
+            }
+            (ColorMode::EscapeTime, _) if value < interior => Some(value),
+            _ => field.interior.get(i).filter(|shade| !shade.is_nan()).map(|shade| shade * span * 0.999),
+        };
+
+        let index = match (index, cycle_offset) {
+            (Some(index), Some(offset)) => Some((index + offset).rem_euclid(span)),
+            (index, _) => index,
+        };
+        let color_char = get_color(index, color_scheme);
 
-            write!(stdout, "{}", color_char)?;
-        }
+        write!(stdout, "{}", color_char)?;
     }
 
     stdout.flush()?;
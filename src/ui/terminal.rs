@@ -1,6 +1,8 @@
-use crossterm::{cursor, execute, terminal};
+use crossterm::{cursor, execute, queue, style, terminal};
 use std::io::{self, Write, stdout};
 
+use crate::app::keymap::Keymap;
+
 pub struct ScreenSize {
     pub width: u16,
     pub height: u16,
@@ -23,17 +25,77 @@ pub fn cleanup_terminal() -> io::Result<()> {
     Ok(())
 }
 
-pub fn display_help() -> io::Result<()> {
+/// Draws the help panel centered over the frame, listing every binding in
+/// `keymap`. Bindings are split into columns when they are too many for the
+/// screen's height; anything that still does not fit is cut off.
+pub fn display_help(keymap: &Keymap, width: u16, height: u16) -> io::Result<()> {
+    let bindings = keymap.help();
+
+    // The border, title, footer and their blank lines take six rows
+    let max_rows = (height as usize).saturating_sub(6).max(1);
+    let column_count = bindings.len().div_ceil(max_rows).max(1);
+    let column_rows = bindings.len().div_ceil(column_count);
+    let columns: Vec<Vec<String>> = bindings
+        .chunks(column_rows.max(1))
+        .map(|chunk| {
+            let key_width = chunk.iter().map(|(keys, _)| keys.chars().count()).max().unwrap_or(0);
+            chunk.iter().map(|(keys, description)| format!("{:>key_width$}  {}", keys, description)).collect()
+        })
+        .collect();
+    let column_widths: Vec<usize> = columns
+        .iter()
+        .map(|column| column.iter().map(|line| line.chars().count()).max().unwrap_or(0))
+        .collect();
+
+    let mut lines = vec!["Julia Set Fractal Screensaver".to_string(), String::new()];
+    for row in 0..column_rows {
+        let cells: Vec<String> = columns
+            .iter()
+            .zip(&column_widths)
+            .filter_map(|(column, &column_width)| column.get(row).map(|line| format!("{:<column_width$}", line)))
+            .collect();
+        lines.push(cells.join("  ").trim_end().to_string());
+    }
+    lines.push(String::new());
+    lines.push("Press any key to close".to_string());
+
+    let inner = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) + 2;
+    let inner = inner.min((width as usize).saturating_sub(2));
+    let rows = lines.len().min((height as usize).saturating_sub(2));
+    let left = (width as usize).saturating_sub(inner + 2) / 2;
+    let top = (height as usize).saturating_sub(rows + 2) / 2;
+
+    let mut stdout = stdout();
+    let border = "\u{2500}".repeat(inner);
+    queue!(stdout, cursor::MoveTo(left as u16, top as u16), style::Print(format!("\u{250c}{}\u{2510}", border)))?;
+    for (row, line) in lines.iter().take(rows).enumerate() {
+        let text: String = format!(" {}", line).chars().take(inner).collect();
+        queue!(
+            stdout,
+            cursor::MoveTo(left as u16, (top + 1 + row) as u16),
+            style::Print(format!("\u{2502}{:<inner$}\u{2502}", text)),
+        )?;
+    }
+    queue!(
+        stdout,
+        cursor::MoveTo(left as u16, (top + 1 + rows) as u16),
+        style::Print(format!("\u{2514}{}\u{2518}", border)),
+    )?;
+
+    stdout.flush()?;
+    Ok(())
+}
+
+pub fn display_status(message: &str, width: u16, height: u16) -> io::Result<()> {
     let mut stdout = stdout();
-    execute!(stdout, cursor::MoveTo(0, 0))?;
-    println!("Julia Set Fractal Screensaver");
-    println!("---------------------------");
-    println!("Commands:");
-    println!("  q: Quit");
-    println!("  c: Change color scheme");
-    println!("  n: New random Julia set");
-    println!("  h: Show/hide this help");
-    println!("Press any key to continue...");
+    let line: String = message.chars().take(width as usize).collect();
+    execute!(
+        stdout,
+        cursor::MoveTo(0, height.saturating_sub(1)),
+        style::SetAttribute(style::Attribute::Reverse),
+        style::Print(line),
+        style::SetAttribute(style::Attribute::Reset),
+    )?;
     stdout.flush()?;
     Ok(())
 }
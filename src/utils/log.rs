@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variable that sets the log level.
+pub const LOG_ENV: &str = "JULIA_LOG";
+/// Recent messages kept for the HUD.
+const RECENT: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.to_ascii_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        }
+    }
+}
+
+/// Diagnostic output that never touches the terminal, which belongs to the
+/// rendered frame. Messages go to an optional file and to a short history
+/// that the HUD can show.
+struct Logger {
+    level: Level,
+    file: Option<File>,
+    recent: VecDeque<String>,
+}
+
+static LOGGER: OnceLock<Mutex<Logger>> = OnceLock::new();
+
+/// Starts logging at `level`, appending to `path` if given. Until this is
+/// called, messages are discarded.
+pub fn init(level: Level, path: Option<&Path>) -> io::Result<()> {
+    let file = path.map(|path| OpenOptions::new().create(true).append(true).open(path)).transpose()?;
+    let logger = Logger { level, file, recent: VecDeque::with_capacity(RECENT) };
+    // A second call keeps the first configuration
+    let _ = LOGGER.set(Mutex::new(logger));
+    Ok(())
+}
+
+pub fn write(level: Level, args: fmt::Arguments) {
+    let Some(mut logger) = LOGGER.get().and_then(|logger| logger.lock().ok()) else {
+        return;
+    };
+    if level > logger.level {
+        return;
+    }
+
+    let message = format!("{} {}", level.name(), args);
+    if let Some(file) = &mut logger.file {
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_millis());
+        // Logging must never take the program down with it
+        let _ = writeln!(file, "{} {}", millis, message);
+    }
+
+    if logger.recent.len() == RECENT {
+        logger.recent.pop_front();
+    }
+    logger.recent.push_back(message);
+}
+
+/// The most recent message, for the HUD.
+pub fn latest() -> Option<String> {
+    LOGGER.get()?.lock().ok()?.recent.back().cloned()
+}
+
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => { $crate::utils::log::write($crate::utils::log::Level::Error, format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { $crate::utils::log::write($crate::utils::log::Level::Warn, format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => { $crate::utils::log::write($crate::utils::log::Level::Info, format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => { $crate::utils::log::write($crate::utils::log::Level::Debug, format_args!($($arg)*)) };
+}
@@ -1,6 +1,17 @@
+pub mod log;
+
 pub mod constants {
     pub const MIN_TRANSITION_TIME: f64 = 5.0;
     pub const MAX_TRANSITION_TIME: f64 = 20.0;
+    pub const STATUS_DURATION: f64 = 3.0;
+    /// Zoom factor applied per key press
+    pub const ZOOM_STEP: f64 = 2.0;
+    /// Furthest the view can be zoomed out
+    pub const MIN_ZOOM: f64 = 0.01;
+    /// Pan distance per key press, as a fraction of the view width
+    pub const PAN_STEP: f64 = 0.1;
+    /// Seconds a frame may take before its coarse passes are drawn
+    pub const PREVIEW_DELAY: f64 = 0.1;
 }
 
 // Re-export useful constants at the module level